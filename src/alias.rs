@@ -0,0 +1,210 @@
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::settings::Settings;
+
+#[derive(Debug, Error)]
+pub enum AliasError {
+    #[error("Alias '{0}' has unbalanced quotes")]
+    InvalidQuoting(String),
+
+    #[error("Alias loop detected: {0}")]
+    Recursive(String),
+
+    #[error("Alias '{alias}' expects argument {{{index}}}")]
+    MissingArgument { alias: String, index: usize },
+}
+
+/// Global options that take a value, so their value isn't taken for the command
+const OPTIONS_WITH_VALUE: &[&str] = &["-C", "--workspace", "--color", "--error-format"];
+
+/// Value of -C/--workspace on the raw command line, if given.
+///
+/// Aliases are read from config before clap runs, so the workspace whose
+/// config applies has to be found by hand.
+pub fn workspace_arg(args: &[String]) -> Option<&Path> {
+    raw_option(args, Some("-C"), "--workspace").map(Path::new)
+}
+
+/// Value of a global option on the raw command line, before clap has run
+pub fn raw_option<'a>(args: &'a [String], short: Option<&str>, long: &str) -> Option<&'a str> {
+    let mut iter = args.iter().skip(1).take_while(|arg| *arg != "--");
+    while let Some(arg) = iter.next() {
+        if Some(arg.as_str()) == short || arg == long {
+            return iter.next().map(String::as_str);
+        }
+        if let Some(value) = arg.strip_prefix(long).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value);
+        }
+        if let Some(value) = short.and_then(|short| arg.strip_prefix(short)) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Expand an alias in command position until a built-in command is reached.
+///
+/// `{1}`, `{2}`, ... in the alias are replaced by the arguments that follow
+/// it; arguments not used by a placeholder are appended. Built-in commands
+/// always win over aliases of the same name.
+pub fn expand(
+    mut args: Vec<String>,
+    command: &clap::Command,
+    settings: &Settings,
+) -> Result<Vec<String>, AliasError> {
+    let Some(index) = command_index(&args) else {
+        return Ok(args);
+    };
+
+    let mut chain: Vec<String> = Vec::new();
+    loop {
+        let name = args[index].clone();
+        if command.find_subcommand(&name).is_some() {
+            break;
+        }
+        let Some(definition) = settings.alias(&name) else {
+            break;
+        };
+
+        if chain.contains(&name) {
+            chain.push(name);
+            return Err(AliasError::Recursive(chain.join(" -> ")));
+        }
+
+        let rest = args.split_off(index + 1);
+        args.pop();
+        args.extend(substitute(&name, &definition, rest)?);
+        chain.push(name);
+    }
+
+    Ok(args)
+}
+
+/// Position of the command name, skipping global options and their values
+fn command_index(args: &[String]) -> Option<usize> {
+    let mut i = 1;
+    while let Some(arg) = args.get(i) {
+        if arg == "--" {
+            return None;
+        }
+        if OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            i += 2;
+        } else if arg.starts_with('-') {
+            i += 1;
+        } else {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn substitute(name: &str, definition: &str, args: Vec<String>) -> Result<Vec<String>, AliasError> {
+    let tokens =
+        shell_words::split(definition).map_err(|_| AliasError::InvalidQuoting(name.to_string()))?;
+    let mut used = vec![false; args.len()];
+    let mut expanded = Vec::with_capacity(tokens.len() + args.len());
+
+    for token in tokens {
+        let mut result = String::new();
+        let mut rest = token.as_str();
+
+        while let Some(open) = rest.find('{') {
+            let after = &rest[open + 1..];
+            let placeholder = after
+                .find('}')
+                .and_then(|close| Some((after[..close].parse::<usize>().ok()?, close)));
+
+            match placeholder {
+                Some((index, close)) => {
+                    let value = index
+                        .checked_sub(1)
+                        .and_then(|i| args.get(i).map(|arg| (i, arg)));
+                    let Some((i, value)) = value else {
+                        return Err(AliasError::MissingArgument {
+                            alias: name.to_string(),
+                            index,
+                        });
+                    };
+                    used[i] = true;
+                    result.push_str(&rest[..open]);
+                    result.push_str(value);
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    result.push_str(&rest[..=open]);
+                    rest = after;
+                }
+            }
+        }
+        result.push_str(rest);
+        expanded.push(result);
+    }
+
+    expanded.extend(
+        args.into_iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(arg, _)| arg),
+    );
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::Cli;
+
+    fn expand_with(aliases: &str, args: &[&str]) -> Result<Vec<String>, AliasError> {
+        let args = args.iter().map(|arg| arg.to_string()).collect();
+        expand(args, &Cli::command(), &Settings::from_toml(aliases))
+    }
+
+    #[test]
+    fn appends_unused_arguments() {
+        let aliases = "[alias]\nd = \"mv {1} done\"\ntodo = \"ls -s todo\"";
+        assert_eq!(expand_with(aliases, &["tix", "d", "01J", "-k"]).unwrap(), ["tix", "mv", "01J", "done", "-k"]);
+        assert_eq!(expand_with(aliases, &["tix", "todo", "-p", "a"]).unwrap(), ["tix", "ls", "-s", "todo", "-p", "a"]);
+    }
+
+    #[test]
+    fn skips_global_options() {
+        let aliases = "[alias]\ntodo = \"ls -s todo\"";
+        assert_eq!(
+            expand_with(aliases, &["tix", "-C", "todo", "--color", "never", "todo"]).unwrap(),
+            ["tix", "-C", "todo", "--color", "never", "ls", "-s", "todo"]
+        );
+        assert_eq!(expand_with(aliases, &["tix", "--", "todo"]).unwrap(), ["tix", "--", "todo"]);
+    }
+
+    #[test]
+    fn quoting_and_placeholders_inside_words() {
+        let aliases = "[alias]\nnote = \"add -t 'Note: {1}' -b {2}\"";
+        assert_eq!(
+            expand_with(aliases, &["tix", "note", "call Bob", "about {1}"]).unwrap(),
+            ["tix", "add", "-t", "Note: call Bob", "-b", "about {1}"]
+        );
+    }
+
+    #[test]
+    fn chains_and_builtins() {
+        let aliases = "[alias]\nls = \"projects\"\nt = \"todo\"\ntodo = \"ls -s todo\"";
+        // Built-in commands win over aliases of the same name
+        assert_eq!(expand_with(aliases, &["tix", "ls"]).unwrap(), ["tix", "ls"]);
+        assert_eq!(expand_with(aliases, &["tix", "t"]).unwrap(), ["tix", "ls", "-s", "todo"]);
+    }
+
+    #[test]
+    fn errors() {
+        let aliases = "[alias]\na = \"b\"\nb = \"a\"\nq = \"ls 'open\"\nd = \"mv {2} done\"";
+        assert!(matches!(expand_with(aliases, &["tix", "a"]), Err(AliasError::Recursive(chain)) if chain == "a -> b -> a"));
+        assert!(matches!(expand_with(aliases, &["tix", "q"]), Err(AliasError::InvalidQuoting(_))));
+        assert!(matches!(
+            expand_with(aliases, &["tix", "d", "01J"]),
+            Err(AliasError::MissingArgument { index: 2, .. })
+        ));
+    }
+}
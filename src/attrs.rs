@@ -0,0 +1,88 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::branch;
+use crate::git::{self, GitError};
+use crate::history::is_ticket_id;
+
+/// An empty `label_<name>` file in the ticket directory tags the ticket
+pub const LABEL_PREFIX: &str = "label_";
+
+/// An `attr_<key>` file in the ticket directory holds one attribute value
+pub const ATTR_PREFIX: &str = "attr_";
+
+/// Labels and attributes of a ticket, kept as files next to title.md the
+/// same way extensions keep theirs (see Philosophy in the README)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Extras {
+    pub labels: BTreeSet<String>,
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Labels and attributes of every ticket on a project branch, by ticket ID
+pub fn read(project: &str) -> Result<BTreeMap<String, Extras>, GitError> {
+    git::ensure_project(project)?;
+    let listing = git::git(&["ls-tree", "-r", project])?;
+
+    let mut extras: BTreeMap<String, Extras> = BTreeMap::new();
+    let mut values = Vec::new();
+    for line in listing.lines() {
+        // <mode> <type> <hash>\t<path>
+        let Some((meta, path)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some((id, file)) = path.split_once('/') else {
+            continue;
+        };
+        if !is_ticket_id(id) {
+            continue;
+        }
+
+        if let Some(label) = file.strip_prefix(LABEL_PREFIX) {
+            let entry = extras.entry(id.to_string()).or_default();
+            entry.labels.insert(decode(label));
+        } else if let Some(key) = file.strip_prefix(ATTR_PREFIX)
+            && let Some(hash) = meta.split_whitespace().nth(2)
+        {
+            values.push((id.to_string(), decode(key), hash.to_string()));
+        }
+    }
+
+    let hashes: Vec<&str> = values.iter().map(|(_, _, hash)| hash.as_str()).collect();
+    let blobs = branch::read_blobs(&hashes)?;
+    for (id, key, hash) in values {
+        let value = blobs.get(&hash).map(|v| v.trim_end().to_string()).unwrap_or_default();
+        extras.entry(id).or_default().attributes.insert(key, value);
+    }
+
+    Ok(extras)
+}
+
+/// Make the label files of a ticket directory match `extras.labels` and
+/// write its attributes, leaving attributes it doesn't name alone
+pub fn write(dir: &Path, extras: &Extras) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_string();
+        if let Some(label) = name.strip_prefix(LABEL_PREFIX)
+            && !extras.labels.contains(&decode(label))
+        {
+            std::fs::remove_file(dir.join(&name))?;
+        }
+    }
+    for label in &extras.labels {
+        std::fs::write(dir.join(format!("{LABEL_PREFIX}{}", encode(label))), "")?;
+    }
+    for (key, value) in &extras.attributes {
+        std::fs::write(dir.join(format!("{ATTR_PREFIX}{}", encode(key))), format!("{value}\n"))?;
+    }
+    Ok(())
+}
+
+/// Escape the characters a file name can't hold
+fn encode(name: &str) -> String {
+    name.replace('%', "%25").replace('/', "%2F").replace('\\', "%5C")
+}
+
+fn decode(name: &str) -> String {
+    name.replace("%2F", "/").replace("%5C", "\\").replace("%25", "%")
+}
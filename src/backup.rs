@@ -0,0 +1,324 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use thiserror::Error;
+
+use crate::branch;
+use crate::ffi::projects::ProjectsError;
+use crate::ffi::{self, TixError};
+use crate::git::{self, GitError};
+use crate::history;
+use crate::projects;
+use crate::workspace;
+
+/// Version of the archive layout; restore refuses newer ones
+const FORMAT: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Every project branch and its history, as written by `git bundle`
+const BUNDLE: &str = "tix.bundle";
+
+/// Workspace files kept in the ticket repository's git directory
+const WORKSPACE_FILES: &[&str] = &["cli.toml", "taskwarrior.json"];
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error(transparent)]
+    TixError(#[from] TixError),
+
+    #[error(transparent)]
+    GitError(#[from] GitError),
+
+    #[error(transparent)]
+    ProjectsError(#[from] ProjectsError),
+
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+
+    #[error("{0} is not a tix backup: {1}")]
+    Invalid(PathBuf, String),
+
+    #[error("Backup {0} failed verification: {1}")]
+    Mismatch(PathBuf, String),
+
+    #[error("{0} already exists, restore into a directory without a workspace")]
+    WorkspaceExists(PathBuf),
+}
+
+/// What a backup holds, stored in the archive as manifest.json
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub tix_version: String,
+    pub created: String,
+    pub current_project: String,
+    pub projects: Vec<ProjectEntry>,
+    /// SHA-256 of every other file in the archive, by name
+    pub checksums: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProjectEntry {
+    pub name: String,
+    /// Commit the project branch pointed at
+    pub head: String,
+    pub tickets: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+}
+
+impl Manifest {
+    pub fn tickets(&self) -> usize {
+        self.projects.iter().map(|p| p.tickets).sum()
+    }
+}
+
+/// A private scratch directory with an unguessable name, created fresh and
+/// removed again when dropped
+fn staging(purpose: &str) -> Result<TempDir, BackupError> {
+    tempfile::Builder::new()
+        .prefix(&format!("tix_{purpose}_"))
+        .tempdir()
+        .map_err(|err| BackupError::Write(std::env::temp_dir(), err))
+}
+
+/// Write every project branch with its full history, the project
+/// descriptions and the workspace settings to a zstd-compressed tar
+/// archive at `path`
+pub fn create(path: &Path) -> Result<Manifest, BackupError> {
+    let info = projects::info()?;
+    let mut entries = Vec::new();
+    for name in ffi::projects()? {
+        let project = info.get(&name);
+        entries.push(ProjectEntry {
+            head: git::git(&["rev-parse", &format!("refs/heads/{name}")])?.trim().to_string(),
+            tickets: branch::tickets(&name)?.len(),
+            description: project.and_then(|p| p.description.clone()),
+            archived: project.is_some_and(|p| p.archived),
+            name,
+        });
+    }
+
+    let staging = staging("backup")?;
+    let bundle = staging.path().join(BUNDLE);
+    git::git(&["bundle", "create", &bundle.to_string_lossy(), "--branches"])?;
+
+    let git_dir = git::tix_dir().join(".git");
+    let mut files = vec![(BUNDLE, bundle)];
+    files.extend(
+        WORKSPACE_FILES
+            .iter()
+            .map(|name| (*name, git_dir.join(name)))
+            .filter(|(_, path)| path.is_file()),
+    );
+    let mut checksums = BTreeMap::new();
+    for (name, path) in &files {
+        let file = File::open(path).map_err(|err| BackupError::Read(path.clone(), err))?;
+        let checksum = copy_hashed(file, std::io::sink()).map_err(|err| BackupError::Read(path.clone(), err))?;
+        checksums.insert(name.to_string(), checksum);
+    }
+
+    let manifest = Manifest {
+        format: FORMAT,
+        tix_version: env!("CARGO_PKG_VERSION").to_string(),
+        created: history::format_timestamp(history::now()),
+        current_project: git::current_branch()?,
+        projects: entries,
+        checksums,
+    };
+
+    // Written beside the target and renamed, so a failed backup never
+    // leaves a truncated archive under the real name
+    let partial = PathBuf::from(format!("{}.partial", path.display()));
+    let written = write_archive(&partial, &manifest, &files).and_then(|()| std::fs::rename(&partial, path));
+    if let Err(err) = written {
+        std::fs::remove_file(&partial).ok();
+        return Err(BackupError::Write(path.to_path_buf(), err));
+    }
+    Ok(manifest)
+}
+
+fn write_archive(path: &Path, manifest: &Manifest, files: &[(&str, PathBuf)]) -> std::io::Result<()> {
+    let encoder = zstd::Encoder::new(File::create(path)?, 0)?;
+    let mut archive = tar::Builder::new(encoder);
+
+    let json = serde_json::to_vec_pretty(manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(history::now().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, MANIFEST, json.as_slice())?;
+
+    for (name, path) in files {
+        archive.append_path_with_name(path, name)?;
+    }
+    archive.into_inner()?.finish()?.sync_all()
+}
+
+/// Verify an archive without restoring it
+pub fn check(archive: &Path) -> Result<Manifest, BackupError> {
+    let staging = staging("check")?;
+    unpack(archive, staging.path())
+}
+
+/// Recreate a workspace in the current directory from an archive, then
+/// check every project against the manifest. Nothing is left behind when
+/// any step fails.
+pub fn restore(archive: &Path) -> Result<(Manifest, PathBuf), BackupError> {
+    let root = std::env::current_dir().map_err(|err| BackupError::Read(PathBuf::from("."), err))?;
+    let tix_dir = root.join(workspace::TIX_DIR_NAME);
+    if tix_dir.exists() {
+        return Err(BackupError::WorkspaceExists(tix_dir));
+    }
+
+    let staging = staging("restore")?;
+    let manifest = unpack(archive, staging.path())?;
+    let restored = (|| {
+        git::git_in(
+            &root,
+            &[
+                "clone",
+                "--quiet",
+                "--branch",
+                &manifest.current_project,
+                &staging.path().join(BUNDLE).to_string_lossy(),
+                workspace::TIX_DIR_NAME,
+            ],
+        )?;
+        for project in manifest.projects.iter().filter(|p| p.name != manifest.current_project) {
+            git::git(&["branch", &project.name, &format!("refs/remotes/origin/{}", project.name)])?;
+        }
+        git::git(&["remote", "remove", "origin"])?;
+
+        for project in &manifest.projects {
+            if let Some(description) = &project.description {
+                git::git(&["config", &format!("branch.{}.description", project.name), description])?;
+            }
+            if project.archived {
+                git::git(&["config", &format!("branch.{}.archived", project.name), "true"])?;
+            }
+        }
+        for name in manifest.checksums.keys().filter(|name| *name != BUNDLE) {
+            let target = tix_dir.join(".git").join(name);
+            std::fs::copy(staging.path().join(name), &target).map_err(|err| BackupError::Write(target, err))?;
+        }
+
+        for project in &manifest.projects {
+            let head = git::git(&["rev-parse", &format!("refs/heads/{}", project.name)])?;
+            let tickets = branch::tickets(&project.name)?.len();
+            if head.trim() != project.head || tickets != project.tickets {
+                return Err(BackupError::Mismatch(
+                    archive.to_path_buf(),
+                    format!("project '{}' did not restore as recorded", project.name),
+                ));
+            }
+        }
+        Ok(())
+    })();
+
+    match restored {
+        Ok(()) => Ok((manifest, tix_dir)),
+        Err(err) => {
+            std::fs::remove_dir_all(&tix_dir).ok();
+            Err(err)
+        }
+    }
+}
+
+/// Unpack an archive into `dir`, checking every file against the manifest
+/// and the bundled branches against the recorded projects
+fn unpack(archive: &Path, dir: &Path) -> Result<Manifest, BackupError> {
+    let invalid = |message: String| BackupError::Invalid(archive.to_path_buf(), message);
+    let mismatch = |message: String| BackupError::Mismatch(archive.to_path_buf(), message);
+
+    let file = File::open(archive).map_err(|err| BackupError::Read(archive.to_path_buf(), err))?;
+    let decoder = zstd::Decoder::new(file).map_err(|err| invalid(err.to_string()))?;
+    let mut tar = tar::Archive::new(decoder);
+
+    let mut manifest: Option<Manifest> = None;
+    let mut checksums = BTreeMap::new();
+    for entry in tar.entries().map_err(|err| invalid(err.to_string()))? {
+        let mut entry = entry.map_err(|err| invalid(err.to_string()))?;
+        let name = entry.path().map_err(|err| invalid(err.to_string()))?.to_string_lossy().into_owned();
+
+        if name == MANIFEST {
+            let mut text = String::new();
+            entry.read_to_string(&mut text).map_err(|err| invalid(err.to_string()))?;
+            manifest = Some(serde_json::from_str(&text).map_err(|err| invalid(format!("{MANIFEST}: {err}")))?);
+        } else if name == BUNDLE || WORKSPACE_FILES.contains(&name.as_str()) {
+            let path = dir.join(&name);
+            let out = File::create(&path).map_err(|err| BackupError::Write(path.clone(), err))?;
+            let checksum = copy_hashed(entry, out).map_err(|err| invalid(err.to_string()))?;
+            checksums.insert(name, checksum);
+        } else {
+            return Err(invalid(format!("unexpected file {name}")));
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| invalid(format!("no {MANIFEST}")))?;
+    if manifest.format > FORMAT {
+        return Err(invalid(format!(
+            "format {} was written by tix {}, this version reads up to format {FORMAT}",
+            manifest.format, manifest.tix_version
+        )));
+    }
+    if !manifest.checksums.contains_key(BUNDLE) {
+        return Err(invalid(format!("no {BUNDLE} in {MANIFEST}")));
+    }
+    for (name, expected) in &manifest.checksums {
+        match checksums.remove(name) {
+            Some(actual) if actual == *expected => {}
+            Some(_) => return Err(mismatch(format!("checksum of {name} does not match"))),
+            None => return Err(mismatch(format!("{name} is missing"))),
+        }
+    }
+    if let Some(name) = checksums.keys().next() {
+        return Err(mismatch(format!("{name} is not listed in {MANIFEST}")));
+    }
+
+    let heads = git::git_in(dir, &["bundle", "list-heads", BUNDLE])?;
+    let heads: BTreeMap<&str, &str> = heads
+        .lines()
+        .filter_map(|line| {
+            let (hash, name) = line.split_once(' ')?;
+            Some((name.strip_prefix("refs/heads/")?, hash))
+        })
+        .collect();
+    for project in &manifest.projects {
+        if heads.get(project.name.as_str()) != Some(&project.head.as_str()) {
+            return Err(mismatch(format!("project '{}' is not in the bundle as recorded", project.name)));
+        }
+    }
+    if !manifest.projects.iter().any(|p| p.name == manifest.current_project) {
+        return Err(invalid(format!("current project '{}' is not listed", manifest.current_project)));
+    }
+    Ok(manifest)
+}
+
+/// Copy everything from `reader` to `writer`, returning the SHA-256 of it
+fn copy_hashed(mut reader: impl Read, mut writer: impl Write) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+    }
+    writer.flush()?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
@@ -0,0 +1,402 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use clap::ValueEnum;
+use thiserror::Error;
+
+use crate::attrs;
+use crate::branch;
+use crate::ffi::{self, Priority, Status, Ticket, TixError};
+use crate::git::{self, GitError};
+use crate::history::is_ticket_id;
+
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error(transparent)]
+    TixError(#[from] TixError),
+
+    #[error(transparent)]
+    GitError(#[from] GitError),
+
+    #[error("{0}: {1}")]
+    Ticket(String, TixError),
+
+    #[error("{0} of {1} tickets failed")]
+    Partial(usize, usize),
+
+    #[error("Invalid filter '{0}': {1}")]
+    InvalidFilter(String, String),
+
+    #[error("Failed on ticket {0}, no tickets were changed: {1}")]
+    RolledBack(String, #[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// What happened to one ticket of a batch
+pub enum Outcome {
+    /// The ticket and a description of the change
+    Changed(Ticket, String),
+    Unchanged(Ticket),
+    Failed(String, TixError),
+}
+
+/// Tickets of the current project by ID
+fn current_tickets() -> Result<HashMap<String, Ticket>, GitError> {
+    let project = git::current_branch()?;
+    Ok(branch::tickets(&project)?
+        .into_iter()
+        .map(|ticket| (ticket.id.clone(), ticket))
+        .collect())
+}
+
+/// Look up every ID once, failing on the first bad one unless `keep_going`
+fn resolve(ids: &[String], keep_going: bool) -> Result<Vec<Result<Ticket, Outcome>>, BatchError> {
+    let mut tickets = current_tickets()?;
+    let mut resolved = Vec::with_capacity(ids.len());
+    let mut seen = HashSet::new();
+
+    for id in ids.iter().filter(|id| seen.insert(id.as_str())) {
+        let ticket = if !is_ticket_id(id) {
+            Err(TixError::InvalidTicketId)
+        } else {
+            tickets.remove(id).ok_or(TixError::TicketNotFound)
+        };
+        match ticket {
+            Ok(ticket) => resolved.push(Ok(ticket)),
+            Err(err) if keep_going => resolved.push(Err(Outcome::Failed(id.clone(), err))),
+            Err(err) => return Err(BatchError::Ticket(id.clone(), err)),
+        }
+    }
+    Ok(resolved)
+}
+
+/// Move tickets to a status through the tix library, then squash the
+/// library's commits into one so a single undo reverts them all. A ticket
+/// the library fails to move resets the project to where it started.
+pub fn mv(ids: &[String], status: Status, keep_going: bool) -> Result<Vec<Outcome>, BatchError> {
+    let base = git::head()?;
+    let mut outcomes = Vec::with_capacity(ids.len());
+    let mut moved = Vec::new();
+
+    for ticket in resolve(ids, keep_going)? {
+        let mut ticket = match ticket {
+            Ok(ticket) => ticket,
+            Err(outcome) => {
+                outcomes.push(outcome);
+                continue;
+            }
+        };
+        if ticket.status == status {
+            outcomes.push(Outcome::Unchanged(ticket));
+            continue;
+        }
+
+        if let Err(err) = ffi::mv(&ticket.id, status) {
+            git::rollback(&base)?;
+            return Err(BatchError::RolledBack(ticket.id.clone(), Box::new(err)));
+        }
+
+        moved.push(ticket.id.clone());
+        let change = format!("{:?} -> {status:?}", ticket.status);
+        ticket.status = status;
+        outcomes.push(Outcome::Changed(ticket, change));
+    }
+
+    if !moved.is_empty() {
+        let message = format!(
+            "mv {} tickets to {status:?}\n\n{}",
+            moved.len(),
+            moved.join("\n")
+        );
+        git::squash(&base, &message)?;
+    }
+    Ok(outcomes)
+}
+
+/// Ticket IDs from the first column of each non-empty line
+pub fn read_ids(input: impl std::io::BufRead) -> std::io::Result<Vec<String>> {
+    let mut ids = Vec::new();
+    for line in input.lines() {
+        if let Some(id) = line?.split_whitespace().next() {
+            ids.push(id.to_string());
+        }
+    }
+    Ok(ids)
+}
+
+/// One `field=value`, `field!=value` or `field~text` condition
+enum Term {
+    Status(Vec<Status>, bool),
+    Priority(Vec<Priority>, bool),
+    /// Has any of the labels, or with `!=` none of them
+    Label(Vec<String>, bool),
+    Title(String),
+    Body(String),
+}
+
+impl Term {
+    fn matches(&self, ticket: &Ticket, labels: &BTreeSet<String>) -> bool {
+        match self {
+            Term::Status(statuses, negated) => statuses.contains(&ticket.status) != *negated,
+            Term::Priority(priorities, negated) => priorities.contains(&ticket.priority) != *negated,
+            Term::Label(names, negated) => names.iter().any(|name| labels.contains(name)) != *negated,
+            Term::Title(text) => ticket.title.to_lowercase().contains(text),
+            Term::Body(text) => ticket
+                .body
+                .as_deref()
+                .is_some_and(|body| body.to_lowercase().contains(text)),
+        }
+    }
+}
+
+/// Conditions a ticket must all meet, e.g. `status=todo,doing label!=ui
+/// title~"login page"`
+pub struct Filter(Vec<Term>);
+
+impl Filter {
+    pub fn parse(conditions: &[String]) -> Result<Filter, BatchError> {
+        let mut words = Vec::new();
+        for condition in conditions {
+            words.extend(shell_words::split(condition).map_err(|_| {
+                BatchError::InvalidFilter(condition.clone(), "unbalanced quotes".to_string())
+            })?);
+        }
+
+        let mut terms = Vec::new();
+        for condition in &words {
+            let invalid = |reason: &str| BatchError::InvalidFilter(condition.to_string(), reason.to_string());
+
+            if let Some((field, text)) = condition.split_once('~') {
+                let text = text.to_lowercase();
+                terms.push(match field {
+                    "title" => Term::Title(text),
+                    "body" => Term::Body(text),
+                    _ => return Err(invalid("~ works on title and body")),
+                });
+                continue;
+            }
+
+            let (field, values, negated) = match condition.split_once("!=") {
+                Some((field, values)) => (field, values, true),
+                None => match condition.split_once('=') {
+                    Some((field, values)) => (field, values, false),
+                    None => return Err(invalid("expected field=value, field!=value or field~text")),
+                },
+            };
+            terms.push(match field {
+                "status" => Term::Status(parse_values(values).map_err(|e| invalid(&e))?, negated),
+                "priority" => Term::Priority(parse_values(values).map_err(|e| invalid(&e))?, negated),
+                "label" => Term::Label(values.split(',').map(str::to_string).collect(), negated),
+                _ => return Err(invalid("unknown field, expected status, priority, label, title or body")),
+            });
+        }
+        Ok(Filter(terms))
+    }
+
+    pub fn matches(&self, ticket: &Ticket, labels: &BTreeSet<String>) -> bool {
+        self.0.iter().all(|term| term.matches(ticket, labels))
+    }
+}
+
+fn parse_values<T: ValueEnum>(values: &str) -> Result<Vec<T>, String> {
+    values.split(',').map(|value| T::from_str(value, true)).collect()
+}
+
+/// Fields a bulk amend sets on every matching ticket
+pub struct Change {
+    pub priority: Option<Priority>,
+    pub status: Option<Status>,
+    pub label: Vec<String>,
+    pub unlabel: Vec<String>,
+}
+
+impl Change {
+    /// The labels a ticket ends up with
+    fn labels(&self, labels: &BTreeSet<String>) -> BTreeSet<String> {
+        let mut labels = labels.clone();
+        labels.extend(self.label.iter().cloned());
+        labels.retain(|label| !self.unlabel.contains(label));
+        labels
+    }
+}
+
+/// Matching tickets of the current project and what a bulk amend would change
+pub fn plan(filter: &Filter, change: &Change) -> Result<Vec<Outcome>, BatchError> {
+    let project = git::current_branch()?;
+    let mut extras = attrs::read(&project)?;
+    let mut outcomes = Vec::new();
+
+    for ticket in branch::tickets(&project)? {
+        let labels = extras.remove(&ticket.id).unwrap_or_default().labels;
+        if !filter.matches(&ticket, &labels) {
+            continue;
+        }
+        let mut changes = Vec::new();
+        if let Some(priority) = change.priority.filter(|p| *p != ticket.priority) {
+            changes.push(format!("priority {:?} -> {priority:?}", ticket.priority));
+        }
+        if let Some(status) = change.status.filter(|s| *s != ticket.status) {
+            changes.push(format!("status {:?} -> {status:?}", ticket.status));
+        }
+        let relabelled = change.labels(&labels);
+        let added: Vec<String> = relabelled.difference(&labels).map(|l| format!("+{l}")).collect();
+        let removed: Vec<String> = labels.difference(&relabelled).map(|l| format!("-{l}")).collect();
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(format!("labels {}", [added, removed].concat().join(" ")));
+        }
+
+        if changes.is_empty() {
+            outcomes.push(Outcome::Unchanged(ticket));
+        } else {
+            outcomes.push(Outcome::Changed(ticket, changes.join(", ")));
+        }
+    }
+    Ok(outcomes)
+}
+
+/// Apply a planned bulk amend through the tix library, then squash the
+/// library's commits into one so a single undo reverts the whole batch.
+/// Labels are written as files and staged into the same commit. Any
+/// failure resets the project to where it started.
+pub fn amend(outcomes: &[Outcome], change: &Change, description: &str) -> Result<usize, BatchError> {
+    let base = git::head()?;
+    let mut extras = attrs::read(&git::current_branch()?)?;
+    let mut amended = Vec::new();
+
+    for outcome in outcomes {
+        let Outcome::Changed(ticket, _) = outcome else {
+            continue;
+        };
+        let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = (|| {
+            if let Some(priority) = change.priority.filter(|p| *p != ticket.priority) {
+                ffi::amend(&ticket.id, None, None, Some(priority))?;
+            }
+            if let Some(status) = change.status.filter(|s| *s != ticket.status) {
+                ffi::mv(&ticket.id, status)?;
+            }
+            let mut ticket_extras = extras.remove(&ticket.id).unwrap_or_default();
+            let labels = change.labels(&ticket_extras.labels);
+            if labels != ticket_extras.labels {
+                ticket_extras.labels = labels;
+                // Attributes are already on disk, only the labels change
+                ticket_extras.attributes.clear();
+                attrs::write(&git::tix_dir().join(&ticket.id), &ticket_extras)?;
+                git::git(&["add", "-A", "--", &ticket.id])?;
+            }
+            Ok(())
+        })();
+
+        if let Err(err) = result {
+            git::rollback(&base)?;
+            return Err(BatchError::RolledBack(ticket.id.clone(), err));
+        }
+        amended.push(ticket.id.clone());
+    }
+
+    let message = format!(
+        "amend {} tickets where {description}\n\n{}",
+        amended.len(),
+        amended.join("\n")
+    );
+    git::squash(&base, &message)?;
+    Ok(amended.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(title: &str, body: Option<&str>, status: Status, priority: Priority) -> Ticket {
+        Ticket {
+            id: "01J0ZQ4S9M8Y6VXKHRTB3C2DNA".to_string(),
+            title: title.to_string(),
+            body: body.map(str::to_string),
+            priority,
+            status,
+        }
+    }
+
+    fn filter(conditions: &[&str]) -> Filter {
+        let conditions: Vec<String> = conditions.iter().map(|c| c.to_string()).collect();
+        Filter::parse(&conditions).unwrap()
+    }
+
+    fn labels(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn ids_from_ls_output() {
+        let input = "01J0ZQ4S9M8Y6VXKHRTB3C2DNA Fix login\n\n  01J0ZQ4S9M8Y6VXKHRTB3C2DNB main Docs\n";
+        assert_eq!(
+            read_ids(input.as_bytes()).unwrap(),
+            ["01J0ZQ4S9M8Y6VXKHRTB3C2DNA", "01J0ZQ4S9M8Y6VXKHRTB3C2DNB"]
+        );
+    }
+
+    #[test]
+    fn status_and_priority() {
+        let login = ticket("Fix login", None, Status::doing, Priority::a);
+        assert!(filter(&["status=todo,doing"]).matches(&login, &labels(&[])));
+        assert!(!filter(&["status!=doing"]).matches(&login, &labels(&[])));
+        assert!(filter(&["priority=a", "status=doing"]).matches(&login, &labels(&[])));
+        assert!(!filter(&["priority=a status=done"]).matches(&login, &labels(&[])));
+        // Values ignore case, field names don't
+        assert!(filter(&["status=DOING"]).matches(&login, &labels(&[])));
+        assert!(Filter::parse(&["STATUS=doing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn text() {
+        let login = ticket("Fix Login page", Some("Users see a 500"), Status::todo, Priority::z);
+        assert!(filter(&["title~login"]).matches(&login, &labels(&[])));
+        assert!(!filter(&["title~logout"]).matches(&login, &labels(&[])));
+        assert!(filter(&["body~500"]).matches(&login, &labels(&[])));
+        assert!(!filter(&["body~500"]).matches(&ticket("No body", None, Status::todo, Priority::z), &labels(&[])));
+    }
+
+    #[test]
+    fn quoted_text() {
+        let login = ticket("Fix Login page", None, Status::todo, Priority::z);
+        let none = labels(&[]);
+        assert!(filter(&[r#"title~"login page""#]).matches(&login, &none));
+        assert!(filter(&["status=todo title~'login page'"]).matches(&login, &none));
+        assert!(!filter(&["title~'page login'"]).matches(&login, &none));
+        assert!(Filter::parse(&["title~'login".to_string()]).is_err());
+    }
+
+    #[test]
+    fn label() {
+        let login = ticket("Fix login", None, Status::todo, Priority::z);
+        let tagged = labels(&["bug", "ui"]);
+        assert!(filter(&["label=bug"]).matches(&login, &tagged));
+        assert!(filter(&["label=docs,ui"]).matches(&login, &tagged));
+        assert!(!filter(&["label=docs"]).matches(&login, &tagged));
+        assert!(!filter(&["label!=ui"]).matches(&login, &tagged));
+        assert!(filter(&["label!=ui"]).matches(&login, &labels(&[])));
+    }
+
+    #[test]
+    fn relabel() {
+        let change = Change {
+            priority: None,
+            status: None,
+            label: vec!["triaged".to_string(), "bug".to_string()],
+            unlabel: vec!["new".to_string()],
+        };
+        assert_eq!(change.labels(&labels(&["bug", "new"])), labels(&["bug", "triaged"]));
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert!(filter(&[]).matches(&ticket("Any", None, Status::done, Priority::c), &labels(&[])));
+    }
+
+    #[test]
+    fn invalid_conditions() {
+        for condition in ["status", "owner=me", "status=later", "priority~a", "priority=x"] {
+            assert!(
+                Filter::parse(&[condition.to_string()]).is_err(),
+                "{condition} should not parse"
+            );
+        }
+    }
+}
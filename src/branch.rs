@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ffi::{Priority, Status, Ticket};
+use crate::git::{self, GitError};
+use crate::history::is_ticket_id;
+
+/// Files of a ticket directory as found in the branch tree
+#[derive(Default)]
+struct Entry {
+    status: Option<Status>,
+    priority: Option<Priority>,
+    title: Option<String>,
+    body: Option<String>,
+}
+
+/// Read every ticket on a project branch without checking it out
+pub fn tickets(project: &str) -> Result<Vec<Ticket>, GitError> {
+    git::ensure_project(project)?;
+    let listing = git::git(&["ls-tree", "-r", project])?;
+
+    let mut entries: BTreeMap<String, Entry> = BTreeMap::new();
+    for line in listing.lines() {
+        // <mode> <type> <hash>\t<path>
+        let Some((meta, path)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some(hash) = meta.split_whitespace().nth(2) else {
+            continue;
+        };
+        let Some((id, file)) = path.split_once('/') else {
+            continue;
+        };
+        if !is_ticket_id(id) {
+            continue;
+        }
+
+        let entry = entries.entry(id.to_string()).or_default();
+        match file {
+            "title.md" => entry.title = Some(hash.to_string()),
+            "body.md" => entry.body = Some(hash.to_string()),
+            _ => {
+                if let Some(s) = file.strip_prefix("s=") {
+                    entry.status = s.bytes().next().map(Status::from);
+                } else if let Some(p) = file.strip_prefix("p=") {
+                    entry.priority = p.bytes().next().map(Priority::from);
+                }
+            }
+        }
+    }
+
+    let hashes: Vec<&str> = entries
+        .values()
+        .flat_map(|e| [e.title.as_deref(), e.body.as_deref()])
+        .flatten()
+        .collect();
+    let blobs = read_blobs(&hashes)?;
+    let content = |hash: Option<String>| hash.and_then(|h| blobs.get(&h).cloned());
+
+    let mut tickets: Vec<Ticket> = entries
+        .into_iter()
+        .map(|(id, entry)| Ticket {
+            id,
+            title: content(entry.title).unwrap_or_default().trim_end().to_string(),
+            body: content(entry.body).filter(|body| !body.trim().is_empty()),
+            priority: entry.priority.unwrap_or(Priority::z),
+            status: entry.status.unwrap_or_default(),
+        })
+        .collect();
+    tickets.sort_by(|a, b| (a.priority as u8, &a.id).cmp(&(b.priority as u8, &b.id)));
+
+    Ok(tickets)
+}
+
+/// Find a single ticket on a project branch
+pub fn find(project: &str, ticket_id: &str) -> Result<Option<Ticket>, GitError> {
+    Ok(tickets(project)?.into_iter().find(|t| t.id == ticket_id))
+}
+
+/// Read many blobs with a single `git cat-file --batch` call
+pub fn read_blobs(hashes: &[&str]) -> Result<HashMap<String, String>, GitError> {
+    let mut blobs = HashMap::new();
+    if hashes.is_empty() {
+        return Ok(blobs);
+    }
+
+    let input = hashes.join("\n") + "\n";
+    let output = git::git_with_input(&["cat-file", "--batch"], input.as_bytes())?;
+
+    // Each blob is "<hash> <type> <size>\n<content>\n"
+    let mut rest = output.as_slice();
+    while let Some(newline) = rest.iter().position(|&b| b == b'\n') {
+        let header = String::from_utf8_lossy(&rest[..newline]).to_string();
+        rest = &rest[newline + 1..];
+
+        let mut parts = header.split(' ');
+        let (Some(hash), Some(_), Some(size)) = (parts.next(), parts.next(), parts.next()) else {
+            // "<hash> missing" has no content
+            continue;
+        };
+        let size = size.parse::<usize>().unwrap_or(0).min(rest.len());
+        blobs.insert(
+            hash.to_string(),
+            String::from_utf8_lossy(&rest[..size]).to_string(),
+        );
+        rest = &rest[(size + 1).min(rest.len())..];
+    }
+
+    Ok(blobs)
+}
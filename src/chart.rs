@@ -0,0 +1,306 @@
+use std::fmt::Write as _;
+
+use chrono::{Duration, Local, NaiveDate};
+use strum::IntoEnumIterator;
+
+use crate::ffi::Status;
+use crate::history::TicketHistory;
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum ChartKind {
+    /// Open tickets remaining per day
+    Burndown,
+    /// Cumulative flow: tickets per status per day
+    Cfd,
+}
+
+/// Number of tickets in each status at the end of a day
+pub struct Day {
+    pub date: NaiveDate,
+    pub counts: Vec<(Status, usize)>,
+}
+
+impl Day {
+    fn count(&self, status: Status) -> usize {
+        self.counts
+            .iter()
+            .find(|(s, _)| *s == status)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    fn open(&self) -> usize {
+        self.total() - self.count(Status::done)
+    }
+}
+
+/// Stacking order for the cumulative flow chart, bottom to top
+const STACK: [(Status, char, &str); 4] = [
+    (Status::done, '█', "#2e7d32"),
+    (Status::doing, '▓', "#f9a825"),
+    (Status::todo, '▒', "#1565c0"),
+    (Status::backlog, '░', "#9e9e9e"),
+];
+
+/// Count tickets per status at the end of each day in the window, or None
+/// when the window reaches back further than dates go
+pub fn daily_counts(tickets: &[TicketHistory], window: i64) -> Option<Vec<Day>> {
+    let today = Local::now().date_naive();
+    let first = today.checked_sub_signed(Duration::try_seconds(window)?)?;
+    let now = Local::now().timestamp();
+
+    let days = first
+        .iter_days()
+        .take_while(|date| *date <= today)
+        .map(|date| {
+            let end_of_day = date
+                .and_hms_opt(23, 59, 59)
+                .and_then(|dt| dt.and_local_timezone(Local).earliest())
+                .map_or(now, |dt| dt.timestamp().min(now));
+
+            let counts = Status::iter()
+                .map(|status| {
+                    let count = tickets
+                        .iter()
+                        .filter(|t| t.status_at(end_of_day) == Some(status))
+                        .count();
+                    (status, count)
+                })
+                .collect();
+
+            Day { date, counts }
+        })
+        .collect();
+    Some(days)
+}
+
+/// Format a date with core.date-format, or as %Y-%m-%d if the config file
+/// holds a format chrono can't use
+fn label(date: NaiveDate, format: &str) -> String {
+    let mut label = String::new();
+    match write!(label, "{}", date.format(format)) {
+        Ok(()) => label,
+        Err(_) => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Render one row per day as a horizontal bar scaled to the given width
+pub fn render(kind: ChartKind, days: &[Day], width: usize) -> String {
+    let mut out = String::new();
+
+    let max = days
+        .iter()
+        .map(|day| match kind {
+            ChartKind::Burndown => day.open(),
+            ChartKind::Cfd => day.total(),
+        })
+        .max()
+        .unwrap_or(0);
+    let count_width = max.to_string().len();
+    let date_format = settings::get().date_format();
+    let labels: Vec<String> = days.iter().map(|day| label(day.date, &date_format)).collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    // label + " " + bar + " " + count
+    let bar_width = width.saturating_sub(label_width + count_width + 2).max(10);
+
+    match kind {
+        ChartKind::Burndown => {
+            let _ = writeln!(out, "Open tickets per day");
+        }
+        ChartKind::Cfd => {
+            let legend: Vec<String> = STACK
+                .iter()
+                .map(|(status, symbol, _)| format!("{symbol} {status:?}"))
+                .collect();
+            let _ = writeln!(out, "{}", legend.join("  "));
+        }
+    }
+
+    for (day, label) in days.iter().zip(&labels) {
+        let mut bar = String::new();
+        let value = match kind {
+            ChartKind::Burndown => {
+                bar.push_str(&"█".repeat(scale(day.open(), max, bar_width)));
+                day.open()
+            }
+            ChartKind::Cfd => {
+                // Scale cumulative boundaries so segments never drift past the total
+                let mut cumulative = 0;
+                let mut drawn = 0;
+                for (status, symbol, _) in STACK {
+                    cumulative += day.count(status);
+                    let end = scale(cumulative, max, bar_width);
+                    bar.extend(std::iter::repeat_n(symbol, end - drawn));
+                    drawn = end;
+                }
+                day.total()
+            }
+        };
+
+        let _ = writeln!(
+            out,
+            "{:<label_width$} {:<bar_width$} {:>count_width$}",
+            label,
+            bar,
+            value,
+        );
+    }
+
+    out
+}
+
+/// Render the chart as a standalone SVG document
+pub fn render_svg(kind: ChartKind, days: &[Day]) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 320.0;
+    const MARGIN: f64 = 40.0;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12">"#
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>"#
+    );
+
+    let max = days
+        .iter()
+        .map(|day| match kind {
+            ChartKind::Burndown => day.open(),
+            ChartKind::Cfd => day.total(),
+        })
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let plot_width = WIDTH - 2.0 * MARGIN;
+    let plot_height = HEIGHT - 2.0 * MARGIN;
+    let step = plot_width / (days.len().max(2) - 1) as f64;
+    let x = |i: usize| MARGIN + i as f64 * step;
+    let y = |value: usize| HEIGHT - MARGIN - value as f64 / max * plot_height;
+
+    // Each series is an area between a lower and an upper boundary
+    let series: Vec<(&str, Vec<usize>, Vec<usize>)> = match kind {
+        ChartKind::Burndown => vec![(
+            "#c62828",
+            vec![0; days.len()],
+            days.iter().map(Day::open).collect(),
+        )],
+        ChartKind::Cfd => {
+            let mut lower = vec![0; days.len()];
+            STACK
+                .iter()
+                .map(|(status, _, colour)| {
+                    let upper: Vec<usize> = days
+                        .iter()
+                        .zip(&lower)
+                        .map(|(day, base)| base + day.count(*status))
+                        .collect();
+                    let area = (*colour, lower.clone(), upper.clone());
+                    lower = upper;
+                    area
+                })
+                .collect()
+        }
+    };
+
+    for (colour, lower, upper) in series {
+        let mut points: Vec<String> = upper
+            .iter()
+            .enumerate()
+            .map(|(i, value)| format!("{:.1},{:.1}", x(i), y(*value)))
+            .collect();
+        points.extend(
+            lower
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, value)| format!("{:.1},{:.1}", x(i), y(*value))),
+        );
+        let _ = writeln!(
+            out,
+            r#"<polygon points="{}" fill="{colour}" fill-opacity="0.85"/>"#,
+            points.join(" ")
+        );
+    }
+
+    // Axes and labels
+    let _ = writeln!(
+        out,
+        r#"<line x1="{MARGIN}" y1="{bottom}" x2="{right}" y2="{bottom}" stroke="black"/>"#,
+        bottom = HEIGHT - MARGIN,
+        right = WIDTH - MARGIN,
+    );
+    let _ = writeln!(
+        out,
+        r#"<line x1="{MARGIN}" y1="{MARGIN}" x2="{MARGIN}" y2="{bottom}" stroke="black"/>"#,
+        bottom = HEIGHT - MARGIN,
+    );
+    let _ = writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+        MARGIN - 4.0,
+        MARGIN + 4.0,
+        max
+    );
+    if let (Some(first), Some(last)) = (days.first(), days.last()) {
+        let _ = writeln!(
+            out,
+            r#"<text x="{MARGIN}" y="{}">{}</text>"#,
+            HEIGHT - MARGIN + 16.0,
+            first.date
+        );
+        let _ = writeln!(
+            out,
+            r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+            WIDTH - MARGIN,
+            HEIGHT - MARGIN + 16.0,
+            last.date
+        );
+    }
+    if let ChartKind::Cfd = kind {
+        for (i, (status, _, colour)) in STACK.iter().enumerate() {
+            let lx = MARGIN + i as f64 * 90.0;
+            let _ = writeln!(
+                out,
+                r#"<rect x="{lx}" y="12" width="10" height="10" fill="{colour}"/><text x="{}" y="21">{status:?}</text>"#,
+                lx + 14.0
+            );
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Terminal width in columns, falling back to $COLUMNS and then 80
+pub fn terminal_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(w), _)| w as usize)
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(80)
+}
+
+fn scale(value: usize, max: usize, width: usize) -> usize {
+    if max == 0 {
+        return 0;
+    }
+    (value * width + max / 2) / max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_fall_back_on_invalid_format() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        assert_eq!(label(date, "%d.%m."), "03.06.");
+        assert_eq!(label(date, "%Q"), "2024-06-03");
+    }
+}
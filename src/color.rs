@@ -0,0 +1,143 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ffi::{Priority, Status};
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Colour when writing to a terminal
+    Auto,
+    /// Always colour
+    Always,
+    /// Never colour
+    Never,
+}
+
+/// Something that gets its own colour, configurable as `color.<key>`
+#[derive(Debug, Clone, Copy)]
+pub enum Role {
+    Id,
+    Header,
+    Project,
+    Current,
+    Priority(Priority),
+    Status(Status),
+}
+
+impl Role {
+    fn key(self) -> String {
+        match self {
+            Role::Id => "color.id".to_string(),
+            Role::Header => "color.header".to_string(),
+            Role::Project => "color.project".to_string(),
+            Role::Current => "color.current".to_string(),
+            Role::Priority(priority) => format!("color.priority.{priority:?}"),
+            Role::Status(status) => format!("color.status.{status:?}"),
+        }
+    }
+
+    fn default_style(self) -> &'static str {
+        match self {
+            Role::Id => "blue",
+            Role::Header => "bold",
+            Role::Project => "cyan",
+            Role::Current => "green",
+            Role::Priority(Priority::a) => "bold red",
+            Role::Priority(Priority::b) => "yellow",
+            Role::Priority(Priority::c) => "green",
+            Role::Priority(_) => "normal",
+            Role::Status(Status::backlog) => "dim",
+            Role::Status(Status::todo) => "normal",
+            Role::Status(Status::doing) => "magenta",
+            Role::Status(Status::done) => "green",
+        }
+    }
+}
+
+/// Config keys for every role, for validation
+pub const KEYS: &[&str] = &[
+    "color.id",
+    "color.header",
+    "color.project",
+    "color.current",
+    "color.priority.a",
+    "color.priority.b",
+    "color.priority.c",
+    "color.priority.z",
+    "color.status.backlog",
+    "color.status.todo",
+    "color.status.doing",
+    "color.status.done",
+];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Decide whether to colour: --color, then core.color, then NO_COLOR, then TTY detection
+pub fn init(choice: Option<ColorChoice>) {
+    let choice = choice
+        .or_else(|| settings::get().color())
+        .unwrap_or(ColorChoice::Auto);
+
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && std::io::stdout().is_terminal()
+        }
+    };
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Wrap text in the role's colour; pad before painting so escapes don't break alignment
+pub fn paint(role: Role, text: &str) -> String {
+    if !enabled() {
+        return text.to_string();
+    }
+
+    let style = settings::get()
+        .style(&role.key())
+        .unwrap_or_else(|| role.default_style().to_string());
+    match parse_style(&style) {
+        Ok(codes) if !codes.is_empty() => format!("\x1b[{}m{text}\x1b[0m", codes.join(";")),
+        _ => text.to_string(),
+    }
+}
+
+/// Parse a style such as "bold red" into SGR codes
+pub fn parse_style(style: &str) -> Result<Vec<&'static str>, String> {
+    style
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "normal" | "none"))
+        .map(|word| {
+            Ok(match word {
+                "bold" => "1",
+                "dim" => "2",
+                "italic" => "3",
+                "underline" => "4",
+                "black" => "30",
+                "red" => "31",
+                "green" => "32",
+                "yellow" => "33",
+                "blue" => "34",
+                "magenta" => "35",
+                "cyan" => "36",
+                "white" => "37",
+                "bright-black" => "90",
+                "bright-red" => "91",
+                "bright-green" => "92",
+                "bright-yellow" => "93",
+                "bright-blue" => "94",
+                "bright-magenta" => "95",
+                "bright-cyan" => "96",
+                "bright-white" => "97",
+                _ => return Err(format!("unknown colour or attribute '{word}'")),
+            })
+        })
+        .collect()
+}
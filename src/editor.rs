@@ -1,5 +1,7 @@
 use crate::ffi::{priority::Priority, status::Status, ticket::Ticket};
-use anyhow::Result;
+use crate::settings;
+use anyhow::{Context, Result};
+use std::path::Path;
 use std::process::Command;
 
 // Embed template at compile time
@@ -12,13 +14,7 @@ pub fn open_editor_for_ticket() -> Result<(String, Option<String>, Priority, Opt
     // Use embedded template
     std::fs::write(&temp_file, TICKET_TEMPLATE)?;
 
-    // Open editor (try EDITOR env var, fallback to vi)
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
-    let status = Command::new(&editor).arg(&temp_file).status()?;
-
-    if !status.success() {
-        anyhow::bail!("Editor exited with error");
-    }
+    run_editor(&temp_file)?;
 
     // Parse the file content
     let content = std::fs::read_to_string(&temp_file)?;
@@ -35,6 +31,24 @@ pub fn open_editor_for_ticket() -> Result<(String, Option<String>, Priority, Opt
     Ok((title, body, priority, status))
 }
 
+/// Open the file in core.editor, falling back to $EDITOR and then vi
+fn run_editor(file: &Path) -> Result<()> {
+    let editor = settings::get()
+        .editor()
+        .or_else(|| std::env::var("EDITOR").ok())
+        .unwrap_or_else(|| "vi".to_string());
+
+    // Allow editors with arguments or quoted paths, e.g. "code --wait"
+    let parts = shell_words::split(&editor).with_context(|| format!("Invalid editor command: {editor}"))?;
+    let (program, args) = parts.split_first().map_or(("vi", &[][..]), |(program, args)| (program.as_str(), args));
+    let status = Command::new(program).args(args).arg(file).status()?;
+
+    if !status.success() {
+        anyhow::bail!("Editor exited with error");
+    }
+    Ok(())
+}
+
 fn parse_ticket_template(content: &str) -> Result<(String, Option<String>, Priority, Option<Status>)> {
     let lines = content.lines();
     let mut title = String::new();
@@ -120,13 +134,7 @@ pub fn open_editor_for_ticket_amend(
 
     std::fs::write(&temp_file, current_template)?;
 
-    // Open editor
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
-    let status = Command::new(&editor).arg(&temp_file).status()?;
-
-    if !status.success() {
-        anyhow::bail!("Editor exited with error");
-    }
+    run_editor(&temp_file)?;
 
     // Parse the file content
     let content = std::fs::read_to_string(&temp_file)?;
@@ -0,0 +1,333 @@
+use crate::alias::AliasError;
+use crate::backup::BackupError;
+use crate::batch::BatchError;
+use crate::export::ExportError;
+use crate::ffi::TixError;
+use crate::ffi::add::AddError;
+use crate::ffi::amend::AmendError;
+use crate::ffi::clone::CloneError;
+use crate::ffi::config::ConfigError;
+use crate::ffi::list::ListError;
+use crate::ffi::log::LogError;
+use crate::ffi::mv::MvError;
+use crate::ffi::project::SwitchError;
+use crate::ffi::projects::ProjectsError;
+use crate::ffi::remote::RemoteError;
+use crate::ffi::show::ShowError;
+use crate::git::GitError;
+use crate::import::ImportError;
+use crate::projects::ProjectError;
+use crate::serve::ServeError;
+use crate::settings::SettingsError;
+use crate::transfer::TransferError;
+
+/// How errors are printed on stderr
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// The error message
+    Text,
+    /// {"code": ..., "kind": ..., "message": ...}
+    Json,
+}
+
+/// What went wrong; the discriminant is the exit code.
+///
+/// These codes are part of the CLI's interface: never renumber a kind,
+/// only add new ones.
+#[derive(Debug, Clone, Copy, PartialEq, strum::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+#[repr(u8)]
+pub enum ErrorKind {
+    Error = 1,
+    Usage = 2,
+    NotARepository = 3,
+    TicketNotFound = 4,
+    InvalidTicketId = 5,
+    InvalidPriority = 6,
+    InvalidStatus = 7,
+    InvalidTitle = 8,
+    InvalidArgument = 9,
+    OutOfMemory = 10,
+    CommandFailed = 11,
+    FileSystemError = 12,
+    LibraryError = 13,
+    InitWorkspaceCreationFailed = 14,
+    InitAccessDenied = 15,
+    InitNotOnMain = 16,
+    ConfigInvalidKey = 17,
+    RemoteAlreadyExists = 18,
+    RemoteInvalidName = 19,
+    ProjectNotFound = 20,
+    ProjectAlreadyExists = 21,
+    AlreadyOnProject = 22,
+    PushRejected = 23,
+    GitUnavailable = 30,
+    GitFailed = 31,
+    SameProject = 32,
+    TicketAlreadyExists = 33,
+    MainProject = 34,
+    CurrentProject = 35,
+    ConfigFile = 36,
+    UnknownConfigKey = 37,
+    InvalidConfigValue = 38,
+    NoWorkspace = 39,
+    NoGlobalConfig = 40,
+    InvalidAlias = 41,
+    AliasLoop = 42,
+    AliasMissingArgument = 43,
+    Io = 44,
+    PartialFailure = 45,
+    InvalidFilter = 46,
+    InvalidImport = 47,
+    DuplicateImport = 48,
+    UnmappedValues = 49,
+    InvalidBackup = 50,
+    BackupMismatch = 51,
+    WorkspaceExists = 52,
+}
+
+impl ErrorKind {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+}
+
+impl From<&TixError> for ErrorKind {
+    fn from(err: &TixError) -> Self {
+        match err {
+            TixError::OutOfMemory => ErrorKind::OutOfMemory,
+            TixError::NotARepository => ErrorKind::NotARepository,
+            TixError::CommandFailed => ErrorKind::CommandFailed,
+            TixError::FileSystemError => ErrorKind::FileSystemError,
+            TixError::InvalidTicketId => ErrorKind::InvalidTicketId,
+            TixError::UnknownError => ErrorKind::LibraryError,
+            TixError::InitWorkspaceCreationFailed => ErrorKind::InitWorkspaceCreationFailed,
+            TixError::InitAccessDenied => ErrorKind::InitAccessDenied,
+            TixError::ConfigInvalidKey => ErrorKind::ConfigInvalidKey,
+            TixError::RemoteAlreadyExists => ErrorKind::RemoteAlreadyExists,
+            TixError::RemoteInvalidName => ErrorKind::RemoteInvalidName,
+            TixError::SwitchProjectNotFound(_) => ErrorKind::ProjectNotFound,
+            TixError::SwitchProjectAlreadyExists(_) => ErrorKind::ProjectAlreadyExists,
+            TixError::SwitchAlreadyOnProject(_) => ErrorKind::AlreadyOnProject,
+            TixError::InvalidPriority => ErrorKind::InvalidPriority,
+            TixError::InvalidStatus => ErrorKind::InvalidStatus,
+            TixError::InvalidTitle => ErrorKind::InvalidTitle,
+            TixError::TicketNotFound => ErrorKind::TicketNotFound,
+            TixError::InitNotOnMain => ErrorKind::InitNotOnMain,
+            TixError::PushRejectedNeedsForce => ErrorKind::PushRejected,
+        }
+    }
+}
+
+impl From<&GitError> for ErrorKind {
+    fn from(err: &GitError) -> Self {
+        match err {
+            GitError::TixError(err) => err.into(),
+            GitError::Spawn(_) => ErrorKind::GitUnavailable,
+            GitError::Failed { .. } => ErrorKind::GitFailed,
+            GitError::ProjectNotFound(_) => ErrorKind::ProjectNotFound,
+        }
+    }
+}
+
+impl From<&TransferError> for ErrorKind {
+    fn from(err: &TransferError) -> Self {
+        match err {
+            TransferError::TixError(err) => err.into(),
+            TransferError::GitError(err) => err.into(),
+            TransferError::SameProject(..) => ErrorKind::SameProject,
+            TransferError::AlreadyExists(..) => ErrorKind::TicketAlreadyExists,
+            TransferError::TempIndex(..) => ErrorKind::Io,
+        }
+    }
+}
+
+impl From<&ProjectError> for ErrorKind {
+    fn from(err: &ProjectError) -> Self {
+        match err {
+            ProjectError::TixError(err) => err.into(),
+            ProjectError::GitError(err) => err.into(),
+            ProjectError::MainProject(_) => ErrorKind::MainProject,
+            ProjectError::CurrentProject(_) => ErrorKind::CurrentProject,
+        }
+    }
+}
+
+impl From<&SettingsError> for ErrorKind {
+    fn from(err: &SettingsError) -> Self {
+        match err {
+            SettingsError::Io(..) | SettingsError::Parse(..) => ErrorKind::ConfigFile,
+            SettingsError::UnknownKey(_) => ErrorKind::UnknownConfigKey,
+            SettingsError::InvalidValue { .. } => ErrorKind::InvalidConfigValue,
+            SettingsError::NoWorkspace => ErrorKind::NoWorkspace,
+            SettingsError::NoGlobalPath => ErrorKind::NoGlobalConfig,
+        }
+    }
+}
+
+impl From<&BatchError> for ErrorKind {
+    fn from(err: &BatchError) -> Self {
+        match err {
+            BatchError::TixError(err) => err.into(),
+            BatchError::GitError(err) => err.into(),
+            BatchError::Ticket(_, err) => err.into(),
+            BatchError::Partial(..) => ErrorKind::PartialFailure,
+            BatchError::InvalidFilter(..) => ErrorKind::InvalidFilter,
+            BatchError::RolledBack(_, err) => kind_of(err.as_ref()).unwrap_or(ErrorKind::Error),
+        }
+    }
+}
+
+impl From<&ImportError> for ErrorKind {
+    fn from(err: &ImportError) -> Self {
+        match err {
+            ImportError::TixError(err) => err.into(),
+            ImportError::GitError(err) => err.into(),
+            ImportError::AddError(err) => err.into(),
+            ImportError::AmendError(err) => err.into(),
+            ImportError::MvError(err) => err.into(),
+            ImportError::SwitchError(err) => err.into(),
+            ImportError::Read(..) | ImportError::Write(..) | ImportError::Save(..) | ImportError::Prompt(_) => {
+                ErrorKind::Io
+            }
+            ImportError::Invalid(..) => ErrorKind::InvalidImport,
+            ImportError::Duplicate(_) => ErrorKind::DuplicateImport,
+            ImportError::Unmapped(_) => ErrorKind::UnmappedValues,
+            ImportError::RolledBack(_, err) => err.as_ref().into(),
+        }
+    }
+}
+
+impl From<&BackupError> for ErrorKind {
+    fn from(err: &BackupError) -> Self {
+        match err {
+            BackupError::TixError(err) => err.into(),
+            BackupError::GitError(err) => err.into(),
+            BackupError::ProjectsError(err) => err.into(),
+            BackupError::Read(..) | BackupError::Write(..) => ErrorKind::Io,
+            BackupError::Invalid(..) => ErrorKind::InvalidBackup,
+            BackupError::Mismatch(..) => ErrorKind::BackupMismatch,
+            BackupError::WorkspaceExists(_) => ErrorKind::WorkspaceExists,
+        }
+    }
+}
+
+impl From<&ServeError> for ErrorKind {
+    fn from(err: &ServeError) -> Self {
+        match err {
+            ServeError::Bind(..) => ErrorKind::Io,
+        }
+    }
+}
+
+impl From<&ExportError> for ErrorKind {
+    fn from(err: &ExportError) -> Self {
+        match err {
+            ExportError::GitError(err) => err.into(),
+            ExportError::ProjectsError(err) => err.into(),
+            ExportError::Write(..) => ErrorKind::Io,
+        }
+    }
+}
+
+impl From<&AliasError> for ErrorKind {
+    fn from(err: &AliasError) -> Self {
+        match err {
+            AliasError::InvalidQuoting(_) => ErrorKind::InvalidAlias,
+            AliasError::Recursive(_) => ErrorKind::AliasLoop,
+            AliasError::MissingArgument { .. } => ErrorKind::AliasMissingArgument,
+        }
+    }
+}
+
+/// Map an ffi command error: its own variants, or the TixError it wraps
+macro_rules! ffi_error_kind {
+    ($ty:ident { $($variant:ident => $kind:ident),* $(,)? }) => {
+        impl From<&$ty> for ErrorKind {
+            fn from(err: &$ty) -> Self {
+                match err {
+                    $ty::TixError(err) => err.into(),
+                    $($ty::$variant => ErrorKind::$kind,)*
+                }
+            }
+        }
+    };
+}
+
+ffi_error_kind!(AddError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(AmendError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(CloneError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(ConfigError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(ListError { InvalidArgument => InvalidArgument, InvalidStatus => InvalidStatus });
+ffi_error_kind!(LogError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(MvError { InvalidArgument => InvalidArgument, InvalidStatus => InvalidStatus });
+ffi_error_kind!(SwitchError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(ProjectsError {});
+ffi_error_kind!(RemoteError { InvalidArgument => InvalidArgument });
+ffi_error_kind!(ShowError { InvalidArgument => InvalidArgument });
+
+/// Classify an error by the first error in its chain that tix knows
+pub fn kind(err: &anyhow::Error) -> ErrorKind {
+    err.chain().find_map(kind_of).unwrap_or(ErrorKind::Error)
+}
+
+fn kind_of(cause: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+    macro_rules! try_kind {
+        ($($ty:ty),*) => {
+            $(if let Some(err) = cause.downcast_ref::<$ty>() {
+                return Some(err.into());
+            })*
+        };
+    }
+
+    try_kind!(
+        TixError,
+        AddError,
+        AmendError,
+        CloneError,
+        ConfigError,
+        ListError,
+        LogError,
+        MvError,
+        SwitchError,
+        ProjectsError,
+        RemoteError,
+        ShowError,
+        GitError,
+        TransferError,
+        ProjectError,
+        SettingsError,
+        BatchError,
+        ImportError,
+        ExportError,
+        BackupError,
+        ServeError,
+        AliasError
+    );
+    cause.is::<std::io::Error>().then_some(ErrorKind::Io)
+}
+
+/// Print an error in the chosen format and exit with its code
+pub fn fail(err: &anyhow::Error, format: ErrorFormat) -> ! {
+    report(kind(err), &err.to_string(), format)
+}
+
+/// Print a message of a known kind in the chosen format and exit with its code
+pub fn report(kind: ErrorKind, message: &str, format: ErrorFormat) -> ! {
+    match format {
+        ErrorFormat::Text => eprintln!("{message}"),
+        ErrorFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "code": kind.code(),
+                "kind": kind.name(),
+                "message": message,
+            })
+        ),
+    }
+    std::process::exit(kind.code())
+}
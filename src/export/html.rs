@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use crate::export::{ExportError, Item, STATUSES, sort_key};
+use crate::ffi::{Priority, Status};
+use crate::history;
+use crate::projects;
+
+const STYLE: &str = "\
+body { font: 15px/1.5 system-ui, sans-serif; margin: 0 auto; max-width: 72rem; padding: 1rem 2rem; color: #222; }
+a { color: #0b5cad; text-decoration: none; }
+a:hover { text-decoration: underline; }
+nav { border-bottom: 1px solid #ddd; padding-bottom: .5rem; margin-bottom: 1rem; }
+nav a { margin-right: 1rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: .3rem .6rem; border-bottom: 1px solid #eee; vertical-align: top; }
+code, pre, .id { font-family: ui-monospace, monospace; font-size: 90%; }
+pre { background: #f6f8fa; padding: .8rem; overflow-x: auto; }
+.board { display: grid; grid-template-columns: repeat(4, 1fr); gap: 1rem; }
+.column { background: #f4f5f7; border-radius: 6px; padding: .5rem; }
+.column h2 { font-size: 1rem; margin: .2rem .3rem .6rem; }
+.card { background: #fff; border-radius: 4px; box-shadow: 0 1px 2px #0002; padding: .5rem; margin-bottom: .5rem; }
+.label { display: inline-block; background: #e1ecf4; border-radius: 3px; padding: 0 .4rem; margin: 0 .2rem .2rem 0; font-size: 85%; }
+.priority-a { color: #c0392b; font-weight: bold; }
+.priority-b { color: #d35400; }
+.muted { color: #777; }
+dl { display: grid; grid-template-columns: max-content 1fr; gap: .2rem 1rem; }
+dt { color: #777; }
+dd { margin: 0; }
+";
+
+/// Write a static site to `dir`: an index of projects, and per project a
+/// ticket list, a board, a history page and one page per ticket. Returns
+/// the number of pages written.
+pub fn write(items: &[Item], dir: &Path) -> Result<usize, ExportError> {
+    let info = projects::info()?;
+    let mut by_project: Vec<(&str, Vec<&Item>)> = Vec::new();
+    for item in items {
+        match by_project.iter_mut().find(|(project, _)| *project == item.project) {
+            Some((_, items)) => items.push(item),
+            None => by_project.push((&item.project, vec![item])),
+        }
+    }
+
+    let mut pages = 0;
+    let mut rows = String::new();
+    for (project, items) in &mut by_project {
+        items.sort_by_key(|item| sort_key(item));
+        let description = info.get(*project).and_then(|p| p.description.as_deref()).unwrap_or("");
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{slug}/index.html\">{name}</a></td><td>{count}</td><td>{description}</td></tr>\n",
+            slug = slug(project),
+            name = escape(project),
+            count = items.len(),
+            description = escape(description),
+        ));
+        pages += write_project(project, items, &dir.join(slug(project)))?;
+    }
+
+    let content = format!(
+        "<h1>Projects</h1>\n<table>\n<tr><th>Project</th><th>Tickets</th><th>Description</th></tr>\n{rows}</table>\n"
+    );
+    save(dir, "style.css", STYLE)?;
+    save(dir, "index.html", &page("Projects", "", "", &content))?;
+    Ok(pages + 1)
+}
+
+fn write_project(project: &str, items: &[&Item], dir: &Path) -> Result<usize, ExportError> {
+    let nav = format!(
+        "<a href=\"../index.html\">Projects</a><a href=\"index.html\">{}</a>\
+         <a href=\"board.html\">Board</a><a href=\"history.html\">History</a>",
+        escape(project)
+    );
+
+    let mut rows = String::new();
+    for item in items {
+        let ticket = &item.ticket;
+        rows.push_str(&format!(
+            "<tr><td class=\"id\"><a href=\"{id}.html\">{id}</a></td><td>{title}</td><td>{status:?}</td>\
+             <td class=\"priority-{priority:?}\">{priority:?}</td><td>{labels}</td></tr>\n",
+            id = ticket.id,
+            title = escape(&ticket.title),
+            status = ticket.status,
+            priority = ticket.priority,
+            labels = labels(item),
+        ));
+    }
+    let index = format!(
+        "<h1>{}</h1>\n<table>\n<tr><th>ID</th><th>Title</th><th>Status</th><th>Priority</th><th>Labels</th></tr>\n\
+         {rows}</table>\n",
+        escape(project)
+    );
+    save(dir, "index.html", &page(project, "../", &nav, &index))?;
+
+    let mut columns = String::new();
+    for status in STATUSES {
+        let cards: String = items
+            .iter()
+            .filter(|item| item.ticket.status == status)
+            .map(|item| {
+                format!(
+                    "<div class=\"card\"><a href=\"{}.html\">{}</a><div><span class=\"priority-{:?}\">{:?}</span> {}</div></div>\n",
+                    item.ticket.id,
+                    escape(&item.ticket.title),
+                    item.ticket.priority,
+                    item.ticket.priority,
+                    labels(item),
+                )
+            })
+            .collect();
+        columns.push_str(&format!("<div class=\"column\"><h2>{status:?}</h2>\n{cards}</div>\n"));
+    }
+    let board = format!("<h1>{} board</h1>\n<div class=\"board\">\n{columns}</div>\n", escape(project));
+    save(dir, "board.html", &page(&format!("{project} board"), "../", &nav, &board))?;
+
+    let titles: HashMap<&str, &str> = items
+        .iter()
+        .map(|item| (item.ticket.id.as_str(), item.ticket.title.as_str()))
+        .collect();
+    let history = format!("<h1>{} history</h1>\n{}", escape(project), history_table(project, &titles)?);
+    save(dir, "history.html", &page(&format!("{project} history"), "../", &nav, &history))?;
+
+    for item in items {
+        let content = ticket_page(item);
+        save(dir, &format!("{}.html", item.ticket.id), &page(&item.ticket.title, "../", &nav, &content))?;
+    }
+    Ok(items.len() + 3)
+}
+
+fn ticket_page(item: &Item) -> String {
+    let ticket = &item.ticket;
+    let mut fields = vec![
+        ("ID", format!("<span class=\"id\">{}</span>", ticket.id)),
+        ("Status", format!("{:?}", ticket.status)),
+        ("Priority", format!("<span class=\"priority-{0:?}\">{0:?}</span>", ticket.priority)),
+    ];
+    if let Some(created) = history::ticket_created(&ticket.id) {
+        fields.push(("Created", date(created)));
+    }
+    if !item.extras.labels.is_empty() {
+        fields.push(("Labels", labels(item)));
+    }
+    for (key, value) in &item.extras.attributes {
+        let value = if value.starts_with("https://") || value.starts_with("http://") {
+            format!("<a href=\"{0}\">{0}</a>", escape(value))
+        } else {
+            escape(value)
+        };
+        fields.push((key, value));
+    }
+
+    let fields: String = fields
+        .iter()
+        .map(|(name, value)| format!("<dt>{}</dt><dd>{value}</dd>\n", escape(name)))
+        .collect();
+    let body = ticket.body.as_deref().map(markdown).unwrap_or_default();
+    format!("<h1>{}</h1>\n<dl>\n{fields}</dl>\n{body}", escape(&ticket.title))
+}
+
+/// Commits newest first, with what each did to which ticket
+fn history_table(project: &str, titles: &HashMap<&str, &str>) -> Result<String, ExportError> {
+    let commits = history::commits(project)?;
+    let mut seen = HashSet::new();
+    let mut rows = Vec::with_capacity(commits.len());
+
+    for commit in &commits {
+        let mut events: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for change in &commit.changes {
+            let id = change.ticket_id.as_str();
+            let event = if !change.added {
+                continue;
+            } else if let Some(status) = change.file.strip_prefix("s=") {
+                let status = Status::from(status.bytes().next().unwrap_or(b'b'));
+                format!("moved to {status:?}")
+            } else if let Some(priority) = change.file.strip_prefix("p=") {
+                let priority = Priority::from(priority.bytes().next().unwrap_or(b'z'));
+                format!("priority {priority:?}")
+            } else if change.file == "title.md" || change.file == "body.md" {
+                "edited".to_string()
+            } else {
+                "updated".to_string()
+            };
+            events.entry(id).or_default().push(event);
+        }
+        // A ticket without a title no longer exists
+        for change in commit.changes.iter().filter(|c| !c.added && c.file == "title.md") {
+            events.entry(&change.ticket_id).or_insert_with(|| vec!["removed".to_string()]);
+        }
+
+        let mut lines = Vec::new();
+        for (id, mut events) in events {
+            if seen.insert(id) && events.iter().any(|e| e != "removed") {
+                events = vec!["created".to_string()];
+            }
+            events.dedup();
+            let ticket = match titles.get(id) {
+                Some(title) => format!("<a href=\"{id}.html\">{}</a>", escape(title)),
+                None => format!("<span class=\"id muted\">{id}</span>"),
+            };
+            lines.push(format!("{ticket} {}", events.join(", ")));
+        }
+        if lines.is_empty() {
+            continue;
+        }
+        rows.push(format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"id muted\">{}</td><td>{}</td></tr>\n",
+            date(commit.timestamp),
+            escape(&commit.author),
+            &commit.hash[..commit.hash.len().min(8)],
+            lines.join("<br>\n"),
+        ));
+    }
+
+    rows.reverse();
+    Ok(format!(
+        "<table>\n<tr><th>Date</th><th>Author</th><th>Commit</th><th>Changes</th></tr>\n{}</table>\n",
+        rows.concat()
+    ))
+}
+
+fn labels(item: &Item) -> String {
+    item.extras
+        .labels
+        .iter()
+        .map(|label| format!("<span class=\"label\">{}</span>", escape(label)))
+        .collect()
+}
+
+/// Render a ticket body. Raw HTML is shown as text and links or images
+/// other than http, https, mailto and relative ones go nowhere, so a ticket
+/// can't add markup or scripts to the site.
+fn markdown(text: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let events = Parser::new_ext(text, options).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Link { link_type, dest_url: "#".into(), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Image { link_type, dest_url: "#".into(), title, id })
+        }
+        event => event,
+    });
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+/// Whether a URL is relative or uses one of the schemes a page may link to.
+/// Browsers ignore whitespace and control characters in a scheme, so those
+/// are dropped before looking for one.
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url.chars().filter(|c| !c.is_ascii_whitespace() && !c.is_control()).collect();
+    let Some((scheme, _)) = url.split_once(':') else {
+        return true;
+    };
+    if scheme.contains(['/', '?', '#']) {
+        return true;
+    }
+    matches!(scheme.to_lowercase().as_str(), "http" | "https" | "mailto")
+}
+
+fn page(title: &str, root: &str, nav: &str, content: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n</head>\n\
+         <body>\n<nav>{nav}</nav>\n{content}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+fn date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Directory name for a project; branch names may hold `/` and other
+/// characters that don't belong in a path or URL
+fn slug(project: &str) -> String {
+    project
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' => (b as char).to_string(),
+            _ => format!("_{b:02X}"),
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn save(dir: &Path, name: &str, contents: &str) -> Result<(), ExportError> {
+    let path = dir.join(name);
+    std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&path, contents))
+        .map_err(|err| ExportError::Write(path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_safe_urls() {
+        for url in ["https://example.com", "HTTP://example.com", "mailto:a@b.c", "other.html", "../a:b", "#top", "?q=a:b"] {
+            assert!(is_safe_url(url), "{url}");
+        }
+    }
+
+    #[test]
+    fn drops_other_schemes() {
+        for url in ["javascript:alert(1)", " JavaScript:x", "java\tscript:x", "data:text/html,x", "vbscript:x", "file:///etc"] {
+            assert!(!is_safe_url(url), "{url}");
+        }
+    }
+
+    #[test]
+    fn neutralises_links_and_images() {
+        let html = markdown("[a](javascript:alert(1)) ![b](data:image/svg+xml,x) [c](https://example.com)");
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("data:"));
+        assert!(html.contains("href=\"https://example.com\""));
+    }
+}
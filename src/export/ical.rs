@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::export::Item;
+use crate::ffi::{Priority, Status};
+use crate::history;
+
+/// RFC 5545 lines are at most 75 octets, longer ones are folded
+const LINE_LIMIT: usize = 75;
+
+/// Write an iCalendar file with one VTODO per ticket. `changed` holds when
+/// each ticket was last changed, by project and ID, and is used as its
+/// DTSTAMP so the file only changes when tickets do.
+pub fn write(
+    items: &[Item],
+    changed: &HashMap<(&str, &str), i64>,
+    name: &str,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//tix//tix {}//EN", env!("CARGO_PKG_VERSION")),
+        format!("X-WR-CALNAME:{}", escape(name)),
+    ];
+
+    for item in items {
+        let ticket = &item.ticket;
+        let created = history::ticket_created(&ticket.id);
+        let stamp = changed
+            .get(&(item.project.as_str(), ticket.id.as_str()))
+            .copied()
+            .or(created)
+            .unwrap_or(0);
+
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}@tix", ticket.id));
+        lines.push(format!("DTSTAMP:{}", date_time(stamp)));
+        if let Some(created) = created {
+            lines.push(format!("CREATED:{}", date_time(created)));
+        }
+        lines.push(format!("LAST-MODIFIED:{}", date_time(stamp)));
+        lines.push(format!("SUMMARY:{}", escape(ticket.title.trim())));
+        if let Some(body) = ticket.body.as_deref().map(str::trim).filter(|body| !body.is_empty()) {
+            lines.push(format!("DESCRIPTION:{}", escape(body)));
+        }
+        let status = match ticket.status {
+            Status::backlog | Status::todo => "NEEDS-ACTION",
+            Status::doing => "IN-PROCESS",
+            Status::done => "COMPLETED",
+        };
+        lines.push(format!("STATUS:{status}"));
+        // 1 is the highest priority, 0 leaves it undefined
+        let priority = match ticket.priority {
+            Priority::a => 1,
+            Priority::b => 5,
+            Priority::c => 9,
+            Priority::z | Priority::None => 0,
+        };
+        lines.push(format!("PRIORITY:{priority}"));
+
+        let categories: Vec<String> = std::iter::once(&item.project)
+            .chain(&item.extras.labels)
+            .map(|category| escape(category))
+            .collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        lines.push("END:VTODO".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write!(writer, "{}\r\n", fold(&line))?;
+    }
+    Ok(())
+}
+
+/// A UTC date-time such as 20240105T120000Z
+fn date_time(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// Escape a TEXT value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// Split a line into chunks of at most 75 octets, continued on lines that
+/// start with a space, without breaking a UTF-8 character
+fn fold(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            out.push_str("\r\n ");
+            // The leading space counts towards the next line
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out
+}
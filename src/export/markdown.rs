@@ -0,0 +1,150 @@
+use std::io::Write;
+use std::path::Path;
+
+use crate::export::{ExportError, Item, STATUSES, last_changed, sort_key};
+use crate::ffi::{Priority, Status};
+use crate::history;
+
+/// Which tickets go into the report; empty lists match everything
+pub struct Filter {
+    pub statuses: Vec<Status>,
+    pub priorities: Vec<Priority>,
+    /// Only tickets changed within this many seconds
+    pub since: Option<i64>,
+}
+
+/// The items the filter matches, sorted by status and priority, with when
+/// each was last changed if `since` is set
+pub fn select<'a>(items: &'a [Item], filter: &Filter) -> Result<Vec<(&'a Item, Option<i64>)>, ExportError> {
+    let changed = match filter.since {
+        Some(window) => Some(last_changed(items, history::now() - window)?),
+        None => None,
+    };
+
+    let mut selected: Vec<(&Item, Option<i64>)> = Vec::new();
+    for item in items {
+        let ticket = &item.ticket;
+        if (!filter.statuses.is_empty() && !filter.statuses.contains(&ticket.status))
+            || (!filter.priorities.is_empty() && !filter.priorities.contains(&ticket.priority))
+        {
+            continue;
+        }
+        match &changed {
+            Some(changed) => match changed.get(&(item.project.as_str(), ticket.id.as_str())) {
+                Some(at) => selected.push((item, Some(*at))),
+                None => continue,
+            },
+            None => selected.push((item, None)),
+        }
+    }
+    selected.sort_by_key(|(item, _)| sort_key(item));
+    Ok(selected)
+}
+
+/// Write one document with a section per status, done first, e.g. for
+/// release notes. `since` is the window the tickets were selected by.
+pub fn write(
+    selected: &[(&Item, Option<i64>)],
+    title: &str,
+    since: Option<i64>,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let multiple_projects = selected.windows(2).any(|pair| pair[0].0.project != pair[1].0.project);
+    match since {
+        Some(window) => writeln!(writer, "# {title}: changed since {}", date(history::now() - window))?,
+        None => writeln!(writer, "# {title}")?,
+    }
+    if selected.is_empty() {
+        writeln!(writer, "\nNo tickets found.")?;
+    }
+
+    for status in STATUSES.iter().rev() {
+        let group: Vec<_> = selected.iter().filter(|(item, _)| item.ticket.status == *status).collect();
+        if group.is_empty() {
+            continue;
+        }
+        writeln!(writer, "\n## {} ({})\n", heading(*status), group.len())?;
+
+        for (item, changed) in group {
+            let ticket = &item.ticket;
+            let mut line = String::from("- ");
+            if multiple_projects {
+                line.push_str(&format!("**{}**: ", item.project));
+            }
+            line.push_str(ticket.title.trim());
+            if !matches!(ticket.priority, Priority::z | Priority::None) {
+                line.push_str(&format!(" ({:?})", ticket.priority));
+            }
+            line.push_str(&format!(" `{}`", ticket.id));
+            if !item.extras.labels.is_empty() {
+                let labels: Vec<&str> = item.extras.labels.iter().map(String::as_str).collect();
+                line.push_str(&format!(" — {}", labels.join(", ")));
+            }
+            if let Some(changed) = changed {
+                line.push_str(&format!(" _(changed {})_", date(*changed)));
+            }
+            writeln!(writer, "{line}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Write `<ID>.md` per ticket into `dir`, its fields as YAML front matter
+/// above the body
+pub fn write_files(selected: &[(&Item, Option<i64>)], dir: &Path) -> Result<(), ExportError> {
+    std::fs::create_dir_all(dir).map_err(|err| ExportError::Write(dir.to_path_buf(), err))?;
+
+    for (item, changed) in selected {
+        let ticket = &item.ticket;
+        // JSON strings are valid YAML scalars and take care of quoting
+        let quote = |text: &str| serde_json::to_string(text).unwrap_or_default();
+
+        let mut front = vec![
+            format!("id: {}", ticket.id),
+            format!("title: {}", quote(ticket.title.trim())),
+            format!("project: {}", quote(&item.project)),
+            format!("status: {:?}", ticket.status),
+            format!("priority: {:?}", ticket.priority),
+        ];
+        if let Some(created) = history::ticket_created(&ticket.id) {
+            front.push(format!("created: {}", history::format_timestamp(created)));
+        }
+        if let Some(changed) = changed {
+            front.push(format!("changed: {}", history::format_timestamp(*changed)));
+        }
+        if !item.extras.labels.is_empty() {
+            let labels: Vec<String> = item.extras.labels.iter().map(|label| quote(label)).collect();
+            front.push(format!("labels: [{}]", labels.join(", ")));
+        }
+        if !item.extras.attributes.is_empty() {
+            front.push("attributes:".to_string());
+            for (key, value) in &item.extras.attributes {
+                front.push(format!("  {}: {}", quote(key), quote(value)));
+            }
+        }
+
+        let mut contents = format!("---\n{}\n---\n", front.join("\n"));
+        let body = ticket.body.as_deref().unwrap_or("").trim();
+        if !body.is_empty() {
+            contents.push_str(&format!("\n{body}\n"));
+        }
+        let path = dir.join(format!("{}.md", ticket.id));
+        std::fs::write(&path, contents).map_err(|err| ExportError::Write(path, err))?;
+    }
+    Ok(())
+}
+
+fn heading(status: Status) -> &'static str {
+    match status {
+        Status::backlog => "Backlog",
+        Status::todo => "Todo",
+        Status::doing => "Doing",
+        Status::done => "Done",
+    }
+}
+
+fn date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::attrs::{self, Extras};
+use crate::branch;
+use crate::history;
+use crate::ffi::projects::ProjectsError;
+use crate::ffi::{self, Priority, Status, Ticket};
+use crate::git::{self, GitError};
+
+pub mod html;
+pub mod ical;
+pub mod markdown;
+pub mod taskwarrior;
+pub mod todotxt;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    GitError(#[from] GitError),
+
+    #[error(transparent)]
+    ProjectsError(#[from] ProjectsError),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(std::path::PathBuf, std::io::Error),
+}
+
+/// Statuses in board order, left to right
+pub const STATUSES: [Status; 4] = [Status::backlog, Status::todo, Status::doing, Status::done];
+
+/// A ticket with its project, labels and attributes
+pub struct Item {
+    pub project: String,
+    pub ticket: Ticket,
+    pub extras: Extras,
+}
+
+/// Tickets of the current project, or of every project, read without
+/// switching
+pub fn collect(all_projects: bool) -> Result<Vec<Item>, ExportError> {
+    let projects = if all_projects {
+        ffi::projects()?
+    } else {
+        vec![git::current_branch()?]
+    };
+
+    let mut items = Vec::new();
+    for project in projects {
+        let mut extras = attrs::read(&project)?;
+        for ticket in branch::tickets(&project)? {
+            items.push(Item {
+                extras: extras.remove(&ticket.id).unwrap_or_default(),
+                project: project.clone(),
+                ticket,
+            });
+        }
+    }
+    Ok(items)
+}
+
+/// When each ticket was last changed, by project and ID, for tickets
+/// changed at or after `cutoff`
+pub fn last_changed(items: &[Item], cutoff: i64) -> Result<HashMap<(&str, &str), i64>, ExportError> {
+    let mut projects: Vec<&str> = items.iter().map(|item| item.project.as_str()).collect();
+    projects.dedup();
+
+    let mut changed = HashMap::new();
+    for project in projects {
+        let ids: HashSet<&str> = items
+            .iter()
+            .filter(|item| item.project == project)
+            .map(|item| item.ticket.id.as_str())
+            .collect();
+        for commit in history::commits(project)?.iter().filter(|c| c.timestamp >= cutoff) {
+            for change in &commit.changes {
+                if let Some(id) = ids.get(change.ticket_id.as_str()) {
+                    changed.insert((project, *id), commit.timestamp);
+                }
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Status in board order, then priority, then age
+pub fn sort_key(item: &Item) -> (usize, u8, String) {
+    let status = STATUSES.iter().position(|s| *s == item.ticket.status).unwrap_or(0);
+    let priority = match item.ticket.priority {
+        Priority::None => Priority::z as u8,
+        priority => priority as u8,
+    };
+    (status, priority, item.ticket.id.clone())
+}
@@ -0,0 +1,131 @@
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::export::Item;
+use crate::ffi::{Priority, Status};
+use crate::history;
+use crate::import::taskwarrior::{DATE_FORMAT, KEY, UuidMap};
+
+/// Taskwarrior's `wait:someday`, which keeps a backlog task waiting
+const SOMEDAY: &str = "99991230T000000Z";
+
+/// A task as read by `task import`
+#[derive(Serialize)]
+struct Task<'a> {
+    uuid: String,
+    description: &'a str,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wait: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Serialize)]
+struct Annotation {
+    entry: String,
+    description: String,
+}
+
+fn task_date(timestamp: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp(timestamp, 0).map(|dt| dt.format(DATE_FORMAT).to_string())
+}
+
+/// RFC 3339 or plain date to Taskwarrior's format
+fn to_task_date(date: &str) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|dt| dt.timestamp())
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp())
+        })
+        .ok()
+        .and_then(task_date)
+}
+
+/// A body written by `tix import taskwarrior` turns back into its
+/// annotations; any other body becomes a single annotation
+fn annotations(body: &str, created: Option<String>) -> Vec<Annotation> {
+    let lines: Vec<&str> = body.lines().filter(|l| !l.trim().is_empty()).collect();
+    let parsed: Option<Vec<Annotation>> = lines
+        .iter()
+        .map(|line| {
+            let (date, description) = line.strip_prefix("- ")?.split_once(": ")?;
+            Some(Annotation {
+                entry: to_task_date(date)?,
+                description: description.to_string(),
+            })
+        })
+        .collect();
+
+    match parsed {
+        Some(parsed) if !parsed.is_empty() => parsed,
+        _ if body.trim().is_empty() => Vec::new(),
+        _ => vec![Annotation {
+            entry: created.unwrap_or_else(|| SOMEDAY.to_string()),
+            description: body.trim().to_string(),
+        }],
+    }
+}
+
+/// Write the tickets as a JSON array for `task import`. Each task keeps the
+/// UUID it was imported with, or gets the ticket's ULID as its UUID, so
+/// importing either way updates instead of duplicating.
+pub fn write(items: &[Item], map: &UuidMap, writer: &mut impl Write) -> std::io::Result<()> {
+    let tasks: Vec<Task> = items
+        .iter()
+        .map(|item| {
+            let ticket = &item.ticket;
+            let attributes = &item.extras.attributes;
+            let entry = history::ticket_created(&ticket.id).and_then(task_date);
+            Task {
+                uuid: attributes
+                    .get(KEY)
+                    .cloned()
+                    .or_else(|| map.uuid(&ticket.id))
+                    .unwrap_or_default(),
+                description: &ticket.title,
+                status: match ticket.status {
+                    Status::done => "completed",
+                    Status::backlog => "waiting",
+                    Status::todo | Status::doing => "pending",
+                },
+                start: (ticket.status == Status::doing).then(|| entry.clone()).flatten(),
+                wait: (ticket.status == Status::backlog).then_some(SOMEDAY),
+                priority: match ticket.priority {
+                    Priority::a => Some("H"),
+                    Priority::b => Some("M"),
+                    Priority::c => Some("L"),
+                    Priority::z | Priority::None => None,
+                },
+                // Tags can't hold whitespace
+                tags: item
+                    .extras
+                    .labels
+                    .iter()
+                    .map(|label| label.split_whitespace().collect::<Vec<_>>().join("_"))
+                    .collect(),
+                due: attributes.get("due").and_then(|due| to_task_date(due)),
+                project: attributes.get("taskwarrior_project").map(String::as_str),
+                annotations: annotations(ticket.body.as_deref().unwrap_or(""), entry.clone()),
+                entry,
+            }
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut *writer, &tasks)?;
+    writeln!(writer)
+}
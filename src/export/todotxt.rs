@@ -0,0 +1,47 @@
+use std::io::Write;
+
+use crate::export::Item;
+use crate::ffi::{Priority, Status};
+use crate::import::todotxt;
+
+/// Write one todo.txt line per ticket, in the form `tix import todotxt`
+/// reads back: `(A) Title +project @label status:doing tix:<ID>`, or
+/// `x Title ... pri:A tix:<ID>` for done tickets. Title words that would
+/// read as markup are escaped with `\`.
+pub fn write(items: &[Item], writer: &mut impl Write) -> std::io::Result<()> {
+    for item in items {
+        let ticket = &item.ticket;
+        let letter = match ticket.priority {
+            Priority::a => Some('A'),
+            Priority::b => Some('B'),
+            Priority::c => Some('C'),
+            Priority::z | Priority::None => None,
+        };
+
+        let mut words = Vec::new();
+        match (ticket.status, letter) {
+            (Status::done, _) => words.push("x".to_string()),
+            (_, Some(letter)) => words.push(format!("({letter})")),
+            (_, None) => {}
+        }
+        words.push(todotxt::escape(&ticket.title));
+        words.push(format!("+{}", item.project));
+        // Contexts can't hold whitespace
+        words.extend(
+            item.extras
+                .labels
+                .iter()
+                .map(|label| format!("@{}", label.split_whitespace().collect::<Vec<_>>().join("_"))),
+        );
+        match (ticket.status, letter) {
+            (Status::done, Some(letter)) => words.push(format!("pri:{letter}")),
+            (Status::backlog, _) => words.push("status:backlog".to_string()),
+            (Status::doing, _) => words.push("status:doing".to_string()),
+            _ => {}
+        }
+        words.push(format!("tix:{}", ticket.id));
+
+        writeln!(writer, "{}", words.join(" "))?;
+    }
+    Ok(())
+}
@@ -1,7 +1,9 @@
+use std::fmt::Debug;
+
 use thiserror::Error;
 
 use crate::ffi::{
-    TIX_INVALID_PRIORITY, TIX_INVALID_TITLE, TIX_INVALID_STATUS, TixError, priority::Priority, status::Status, tix_add, tix_add_free,
+    TIX_INVALID_PRIORITY, TIX_INVALID_TITLE, TIX_INVALID_STATUS, TixError, priority::Priority, status::Status, trace, tix_add, tix_add_free,
 };
 
 #[derive(Debug, Error)]
@@ -29,7 +31,13 @@ pub fn add(title: &str, body: Option<&str>, priority: Priority, status: Option<S
         None => 0, // Let tix lib handle default
     };
 
-    let result = unsafe {
+    let args: [(&str, &dyn Debug); 4] = [
+        ("title", &title),
+        ("body", &body),
+        ("priority", &priority),
+        ("status", &status),
+    ];
+    let result = trace::call("tix_add", &args, || unsafe {
         tix_add(
             c_title.as_ptr(),
             c_body.as_ptr(),
@@ -37,7 +45,7 @@ pub fn add(title: &str, body: Option<&str>, priority: Priority, status: Option<S
             status_byte,
             &mut value_ptr,
         )
-    };
+    });
 
     match result {
         0 => {
@@ -50,7 +58,7 @@ pub fn add(title: &str, body: Option<&str>, priority: Priority, status: Option<S
 
             // SAFETY: value_ptr was allocated by tix_add and must be freed
             unsafe { tix_add_free(value_ptr) };
-            Ok(format!("Created ticket: {value}"))
+            Ok(value)
         }
 
         TIX_INVALID_PRIORITY => Err(AddError::TixError(TixError::InvalidPriority)),
@@ -1,6 +1,8 @@
+use std::fmt::Debug;
+
 use thiserror::Error;
 
-use crate::ffi::{Priority, TIX_INVALID_PRIORITY, TIX_INVALID_STATUS, TixError, tix_amend};
+use crate::ffi::{Priority, TIX_INVALID_PRIORITY, TIX_INVALID_STATUS, TixError, trace, tix_amend};
 
 #[derive(Debug, Error)]
 pub enum AmendError {
@@ -27,14 +29,20 @@ pub fn amend(
         None => std::ffi::CString::new("").unwrap(),
     };
 
-    let result = unsafe {
+    let args: [(&str, &dyn Debug); 4] = [
+        ("ticket_id", &ticket_id),
+        ("title", &title),
+        ("body", &body),
+        ("priority", &priority),
+    ];
+    let result = trace::call("tix_amend", &args, || unsafe {
         tix_amend(
             c_ticket_id.as_ptr(),
             c_title.as_ptr(),
             c_body.as_ptr(),
             priority.map(|p| p as u8).unwrap_or(0),
         )
-    };
+    });
 
     match result {
         0 => Ok(()),
@@ -1,4 +1,4 @@
-use crate::ffi::{TIX_OUT_OF_MEMORY, TIX_COMMAND_FAILED, TixError, tix_clone};
+use crate::ffi::{TIX_OUT_OF_MEMORY, TIX_COMMAND_FAILED, TixError, trace, tix_clone};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,7 +13,9 @@ pub enum CloneError {
 pub fn clone(repo_url: &str) -> Result<String, CloneError> {
     let c_url = std::ffi::CString::new(repo_url).map_err(|_| CloneError::InvalidArgument)?;
     
-    let result = unsafe { tix_clone(c_url.as_ptr()) };
+    let result = trace::call("tix_clone", &[("url", &repo_url)], || unsafe {
+        tix_clone(c_url.as_ptr())
+    });
 
     match result {
         0 => Ok("Repository cloned successfully".to_string()),
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::ffi::{TIX_CONFIG_INVALID_KEY, TixError, tix_config_get_free, tix_config_set};
+use crate::ffi::{TIX_CONFIG_INVALID_KEY, TixError, trace, tix_config_get_free, tix_config_set};
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -16,7 +16,11 @@ pub fn config_set(key: &str, value: &str) -> Result<(), ConfigError> {
     let c_key = std::ffi::CString::new(key).map_err(|_| ConfigError::InvalidArgument)?;
     let c_value = std::ffi::CString::new(value).map_err(|_| ConfigError::InvalidArgument)?;
 
-    let result = unsafe { tix_config_set(c_key.as_ptr(), c_value.as_ptr()) };
+    let result = trace::call(
+        "tix_config_set",
+        &[("key", &key), ("value", &value)],
+        || unsafe { tix_config_set(c_key.as_ptr(), c_value.as_ptr()) },
+    );
 
     match result {
         0 => Ok(()),
@@ -33,7 +37,9 @@ pub fn config_get(key: &str) -> Result<String, ConfigError> {
 
     // create a pointer to hold the result. This will be freed by rust
     let mut value_ptr = std::ptr::null_mut();
-    let result = unsafe { crate::ffi::tix_config_get(c_key.as_ptr(), &mut value_ptr) };
+    let result = trace::call("tix_config_get", &[("key", &key)], || unsafe {
+        crate::ffi::tix_config_get(c_key.as_ptr(), &mut value_ptr)
+    });
 
     match result {
         0 => {
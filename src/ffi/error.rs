@@ -4,7 +4,7 @@ use thiserror::Error;
 pub enum TixError {
     #[error("Out of memory")]
     OutOfMemory,
-    #[error("Not a tix repository")]
+    #[error("Not a tix repository ({})", crate::workspace::describe())]
     NotARepository,
     #[error("Command failed")]
     CommandFailed,
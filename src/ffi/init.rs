@@ -1,4 +1,4 @@
-use crate::ffi::{TIX_INIT_ACCESS_DENIED, TIX_INIT_WORKSPACE_CREATION_FAILED, TIX_INIT_NOT_ON_MAIN_BRANCH, TixError, tix_init};
+use crate::ffi::{TIX_INIT_ACCESS_DENIED, TIX_INIT_WORKSPACE_CREATION_FAILED, TIX_INIT_NOT_ON_MAIN_BRANCH, TixError, trace, tix_init};
 use strum::Display;
 
 /// Result of initializing a tix workspace
@@ -13,7 +13,7 @@ pub enum InitResult {
 pub fn init() -> Result<InitResult, TixError> {
     // SAFETY: tix_init is a simple function that doesn't take any pointers
     // and only returns an integer status code
-    let result = unsafe { tix_init() };
+    let result = trace::call("tix_init", &[], || unsafe { tix_init() });
 
     match result {
         0 => Ok(InitResult::Initialized),
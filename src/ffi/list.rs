@@ -4,7 +4,7 @@ use thiserror::Error;
 
 use crate::ffi::{
     Priority, Status, TIX_INVALID_PRIORITY, TIX_INVALID_STATUS, Ticket, TixError,
-    priority::PriorityVecExt, status::StatusVecExt, tix_list, tix_list_free,
+    priority::PriorityVecExt, status::StatusVecExt, trace, tix_list, tix_list_free,
 };
 
 #[derive(Debug, Error)]
@@ -29,7 +29,11 @@ pub fn list(
 
     let mut output = std::ptr::null_mut();
     let mut count: usize = 0;
-    let result = unsafe { tix_list(c_key.as_ptr(), c_val.as_ptr(), &mut output, &mut count) };
+    let result = trace::call(
+        "tix_list",
+        &[("statuses", &statuses), ("priorities", &priorities)],
+        || unsafe { tix_list(c_key.as_ptr(), c_val.as_ptr(), &mut output, &mut count) },
+    );
 
     match result {
         0 => {
@@ -1,4 +1,4 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, tix_log, tix_log_free};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, trace, tix_log, tix_log_free};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -23,7 +23,11 @@ pub fn log(oneline: bool, limit: Option<i32>, since: Option<&str>) -> Result<Str
     let since_ptr = c_since.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
 
     let mut output_ptr = std::ptr::null_mut();
-    let result = unsafe { tix_log(&mut output_ptr, oneline_flag, limit_value, since_ptr) };
+    let result = trace::call(
+        "tix_log",
+        &[("oneline", &oneline), ("limit", &limit), ("since", &since)],
+        || unsafe { tix_log(&mut output_ptr, oneline_flag, limit_value, since_ptr) },
+    );
 
     match result {
         0 => {
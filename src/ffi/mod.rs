@@ -8,6 +8,7 @@
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
 pub mod error;
+pub mod trace;
 pub use error::TixError;
 
 pub mod init;
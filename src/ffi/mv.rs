@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::ffi::{Status, TIX_INVALID_STATUS, TIX_INVALID_TICKET_ID, TixError, tix_move};
+use crate::ffi::{Status, TIX_INVALID_STATUS, TIX_INVALID_TICKET_ID, TixError, trace, tix_move};
 
 #[derive(Debug, Error)]
 pub enum MvError {
@@ -15,7 +15,11 @@ pub enum MvError {
 pub fn mv(ticket_id: &str, status: Status) -> Result<(), MvError> {
     let c_ticket_id = std::ffi::CString::new(ticket_id).map_err(|_| MvError::InvalidArgument)?;
 
-    let result = unsafe { tix_move(c_ticket_id.as_ptr(), status as u8) };
+    let result = trace::call(
+        "tix_move",
+        &[("ticket_id", &ticket_id), ("status", &status)],
+        || unsafe { tix_move(c_ticket_id.as_ptr(), status as u8) },
+    );
 
     match result {
         0 => Ok(()),
@@ -2,7 +2,7 @@ use thiserror::Error;
 
 use crate::ffi::{
     TIX_SWITCH_ALREADY_ON_PROJECT, TIX_SWITCH_PROJECT_ALREADY_EXISTS, TIX_SWITCH_PROJECT_NOT_FOUND,
-    TixError, tix_switch_project,
+    TixError, trace, tix_switch_project,
 };
 
 #[derive(Debug, Error)]
@@ -25,7 +25,11 @@ pub enum SwitchResult {
 pub fn switch(project: &str, create: bool) -> Result<SwitchResult, SwitchError> {
     let project_ptr = std::ffi::CString::new(project).map_err(|_| SwitchError::InvalidArgument)?;
     let create_flag = if create { 1 } else { 0 };
-    let result = unsafe { tix_switch_project(project_ptr.as_ptr(), create_flag) };
+    let result = trace::call(
+        "tix_switch_project",
+        &[("project", &project), ("create", &create)],
+        || unsafe { tix_switch_project(project_ptr.as_ptr(), create_flag) },
+    );
 
     match result {
         0 => Ok(SwitchResult::Switched(project.to_string())),
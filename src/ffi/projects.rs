@@ -1,4 +1,4 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, tix_projects, tix_projects_free};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, trace, tix_projects, tix_projects_free};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -11,7 +11,9 @@ pub fn projects() -> Result<Vec<String>, ProjectsError> {
     let mut output_ptr = std::ptr::null_mut();
     let mut count = 0usize;
 
-    let result = unsafe { tix_projects(&mut output_ptr, &mut count) };
+    let result = trace::call("tix_projects", &[], || unsafe {
+        tix_projects(&mut output_ptr, &mut count)
+    });
 
     match result {
         0 => {
@@ -23,8 +25,8 @@ pub fn projects() -> Result<Vec<String>, ProjectsError> {
 
             let array = unsafe { std::slice::from_raw_parts(output_ptr, count) };
             
-            for i in 0..count {
-                let c_str = unsafe { std::ffi::CStr::from_ptr(array[i]) };
+            for &ptr in array {
+                let c_str = unsafe { std::ffi::CStr::from_ptr(ptr) };
                 projects.push(c_str.to_string_lossy().to_string());
             }
 
@@ -1,7 +1,7 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, tix_pull};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, trace, tix_pull};
 
 pub fn pull() -> Result<String, TixError> {
-    let result = unsafe { tix_pull() };
+    let result = trace::call("tix_pull", &[], || unsafe { tix_pull() });
 
     match result {
         0 => Ok("Pulled changes from remote".to_string()),
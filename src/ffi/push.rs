@@ -1,10 +1,14 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TIX_PUSH_REJECTED_NEEDS_FORCE, TixError, tix_push};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TIX_PUSH_REJECTED_NEEDS_FORCE, TixError, trace, tix_push};
 
 pub fn push(force: bool, force_with_lease: bool) -> Result<String, TixError> {
     let force_flag = if force { 1 } else { 0 };
     let force_with_lease_flag = if force_with_lease { 1 } else { 0 };
     
-    let result = unsafe { tix_push(force_flag, force_with_lease_flag) };
+    let result = trace::call(
+        "tix_push",
+        &[("force", &force), ("force_with_lease", &force_with_lease)],
+        || unsafe { tix_push(force_flag, force_with_lease_flag) },
+    );
 
     match result {
         0 => Ok("Pushed changes to remote".to_string()),
@@ -1,9 +1,9 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, tix_redo};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, trace, tix_redo};
 
 pub fn redo() -> Result<String, TixError> {
     // SAFETY: tix_redo is a simple function that doesn't take any pointers
     // and only returns an integer status code
-    let result = unsafe { tix_redo() };
+    let result = trace::call("tix_redo", &[], || unsafe { tix_redo() });
 
     match result {
         0 => Ok("Redid last undone change".to_string()),
@@ -1,4 +1,4 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TIX_REMOTE_ALREADY_EXISTS, TIX_REMOTE_INVALID_NAME, TixError, tix_remote, tix_remote_free, tix_remote_add};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TIX_REMOTE_ALREADY_EXISTS, TIX_REMOTE_INVALID_NAME, TixError, trace, tix_remote, tix_remote_free, tix_remote_add};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -14,7 +14,9 @@ pub fn remote(verbose: bool) -> Result<String, RemoteError> {
     let verbose_flag = if verbose { 1 } else { 0 };
     let mut output_ptr = std::ptr::null_mut();
     
-    let result = unsafe { tix_remote(verbose_flag, &mut output_ptr) };
+    let result = trace::call("tix_remote", &[("verbose", &verbose)], || unsafe {
+        tix_remote(verbose_flag, &mut output_ptr)
+    });
 
     match result {
         0 => {
@@ -36,7 +38,9 @@ pub fn remote(verbose: bool) -> Result<String, RemoteError> {
 pub fn remote_add(url: &str) -> Result<String, RemoteError> {
     let c_url = std::ffi::CString::new(url).map_err(|_| RemoteError::InvalidArgument)?;
     
-    let result = unsafe { tix_remote_add(c_url.as_ptr()) };
+    let result = trace::call("tix_remote_add", &[("url", &url)], || unsafe {
+        tix_remote_add(c_url.as_ptr())
+    });
 
     match result {
         0 => Ok("Added remote 'origin'".to_string()),
@@ -2,7 +2,7 @@ use thiserror::Error;
 
 use crate::ffi::{
     Priority, Status, TIX_INVALID_TICKET_ID, TIX_OUT_OF_MEMORY, TIX_TICKET_NOT_FOUND, Ticket,
-    TixError, tix_show, tix_show_body, tix_show_body_free, tix_show_priority, tix_show_status,
+    TixError, trace, tix_show, tix_show_body, tix_show_body_free, tix_show_priority, tix_show_status,
     tix_show_title, tix_show_title_free,
 };
 
@@ -19,7 +19,9 @@ pub fn show(ticket_id: &str) -> Result<Ticket, ShowError> {
     let c_ticket_id = std::ffi::CString::new(ticket_id).map_err(|_| ShowError::InvalidArgument)?;
     let mut output = std::ptr::null_mut();
 
-    let result = unsafe { tix_show(c_ticket_id.as_ptr(), &mut output) };
+    let result = trace::call("tix_show", &[("ticket_id", &ticket_id)], || unsafe {
+        tix_show(c_ticket_id.as_ptr(), &mut output)
+    });
     // Implementation of the show command
 
     match result {
@@ -49,7 +51,9 @@ pub fn show_title(ticket_id: &str) -> Result<String, ShowError> {
     let c_ticket_id = std::ffi::CString::new(ticket_id).map_err(|_| ShowError::InvalidArgument)?;
     let mut output = std::ptr::null_mut();
 
-    let result = unsafe { tix_show_title(c_ticket_id.as_ptr(), &mut output) };
+    let result = trace::call("tix_show_title", &[("ticket_id", &ticket_id)], || unsafe {
+        tix_show_title(c_ticket_id.as_ptr(), &mut output)
+    });
 
     match result {
         0 => {
@@ -76,7 +80,9 @@ pub fn show_body(ticket_id: &str) -> Result<String, ShowError> {
     let c_ticket_id = std::ffi::CString::new(ticket_id).map_err(|_| ShowError::InvalidArgument)?;
     let mut output = std::ptr::null_mut();
 
-    let result = unsafe { tix_show_body(c_ticket_id.as_ptr(), &mut output) };
+    let result = trace::call("tix_show_body", &[("ticket_id", &ticket_id)], || unsafe {
+        tix_show_body(c_ticket_id.as_ptr(), &mut output)
+    });
 
     match result {
         0 => {
@@ -102,7 +108,9 @@ pub fn show_body(ticket_id: &str) -> Result<String, ShowError> {
 pub fn show_status(ticket_id: &str) -> Result<Status, ShowError> {
     let c_ticket_id = std::ffi::CString::new(ticket_id).map_err(|_| ShowError::InvalidArgument)?;
 
-    let result = unsafe { tix_show_status(c_ticket_id.as_ptr()) };
+    let result = trace::call("tix_show_status", &[("ticket_id", &ticket_id)], || unsafe {
+        tix_show_status(c_ticket_id.as_ptr())
+    });
 
     if result < 0 {
         match result {
@@ -118,7 +126,9 @@ pub fn show_status(ticket_id: &str) -> Result<Status, ShowError> {
 pub fn show_priority(ticket_id: &str) -> Result<Priority, ShowError> {
     let c_ticket_id = std::ffi::CString::new(ticket_id).map_err(|_| ShowError::InvalidArgument)?;
 
-    let result = unsafe { tix_show_priority(c_ticket_id.as_ptr()) };
+    let result = trace::call("tix_show_priority", &[("ticket_id", &ticket_id)], || unsafe {
+        tix_show_priority(c_ticket_id.as_ptr())
+    });
 
     if result < 0 {
         match result {
@@ -1,8 +1,10 @@
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, clap::ValueEnum, strum::EnumIter)]
 pub enum Status {
     #[value(name = "backlog")]
     backlog = b'b',
+    /// Also used when a ticket's status is missing or unknown
+    #[default]
     #[value(name = "todo")]
     todo = b't',
     #[value(name = "doing")]
@@ -18,7 +20,7 @@ impl From<u8> for Status {
             b't' => Status::todo,
             b'w' => Status::doing,
             b'd' => Status::done,
-            _ => Status::todo, // default
+            _ => Status::default(),
         }
     }
 }
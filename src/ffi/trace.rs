@@ -0,0 +1,19 @@
+use std::ffi::c_int;
+use std::fmt::Debug;
+
+use crate::verbosity::{self, debug};
+
+/// Call a tix library function, tracing its arguments and return code at
+/// -vv and recording its duration for --timings
+pub fn call(name: &str, args: &[(&str, &dyn Debug)], f: impl FnOnce() -> c_int) -> c_int {
+    debug!(
+        "{name}({})",
+        args.iter()
+            .map(|(arg, value)| format!("{arg}={value:?}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let result = verbosity::timed(|| name.to_string(), f);
+    debug!("{name} returned {result}");
+    result
+}
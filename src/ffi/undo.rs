@@ -1,9 +1,9 @@
-use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, tix_undo};
+use crate::ffi::{TIX_NOT_A_REPOSITORY, TIX_COMMAND_FAILED, TixError, trace, tix_undo};
 
 pub fn undo() -> Result<String, TixError> {
     // SAFETY: tix_undo is a simple function that doesn't take any pointers
     // and only returns an integer status code
-    let result = unsafe { tix_undo() };
+    let result = trace::call("tix_undo", &[], || unsafe { tix_undo() });
 
     match result {
         0 => Ok("Undid last change".to_string()),
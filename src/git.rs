@@ -0,0 +1,155 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use thiserror::Error;
+
+use crate::ffi::TixError;
+use crate::verbosity::{self, info};
+use crate::workspace;
+
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error(transparent)]
+    TixError(#[from] TixError),
+
+    #[error("Failed to run git: {0}")]
+    Spawn(#[from] std::io::Error),
+
+    #[error("git {command} failed: {stderr}")]
+    Failed {
+        command: String,
+        /// Exit status, None when git was killed by a signal
+        code: Option<i32>,
+        stderr: String,
+    },
+
+    #[error("Project '{0}' not found")]
+    ProjectNotFound(String),
+}
+
+/// Path of the ticket repository managed by the tix library
+pub fn tix_dir() -> PathBuf {
+    match workspace::root() {
+        Some(root) => root.join(workspace::TIX_DIR_NAME),
+        None => PathBuf::from(workspace::TIX_DIR_NAME),
+    }
+}
+
+/// A git command running inside the ticket repository
+fn command(args: &[&str]) -> Result<Command, GitError> {
+    let dir = tix_dir();
+    if !dir.is_dir() {
+        return Err(TixError::NotARepository.into());
+    }
+
+    info!("running git {}", args.join(" "));
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir).args(args);
+    Ok(command)
+}
+
+/// Run git inside the ticket repository and return its stdout
+pub fn git(args: &[&str]) -> Result<String, GitError> {
+    run(&mut command(args)?, args)
+}
+
+/// Run git against an alternate index file, leaving the working tree untouched
+pub fn git_with_index(index: &Path, args: &[&str]) -> Result<String, GitError> {
+    run(command(args)?.env("GIT_INDEX_FILE", index), args)
+}
+
+/// Run git in another directory, for work outside the ticket repository
+pub fn git_in(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    info!("running git {} in {}", args.join(" "), dir.display());
+    run(Command::new("git").arg("-C").arg(dir).args(args), args)
+}
+
+fn run(command: &mut Command, args: &[&str]) -> Result<String, GitError> {
+    let output = verbosity::timed(|| timing_name(args), || command.output())?;
+
+    if !output.status.success() {
+        return Err(GitError::Failed {
+            command: args.first().unwrap_or(&"").to_string(),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Run git inside the ticket repository, feeding it stdin, and return raw stdout
+pub fn git_with_input(args: &[&str], input: &[u8]) -> Result<Vec<u8>, GitError> {
+    let mut child = command(args)?
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Write from a separate thread so a full stdout pipe can't deadlock us
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    let output = verbosity::timed(|| timing_name(args), || child.wait_with_output())?;
+    writer.join().expect("stdin writer panicked")?;
+
+    if !output.status.success() {
+        return Err(GitError::Failed {
+            command: args.first().unwrap_or(&"").to_string(),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(output.stdout)
+}
+
+fn timing_name(args: &[&str]) -> String {
+    format!("git {}", args.first().unwrap_or(&""))
+}
+
+/// Name of the checked-out project branch
+pub fn current_branch() -> Result<String, GitError> {
+    Ok(git(&["rev-parse", "--abbrev-ref", "HEAD"])?.trim().to_string())
+}
+
+/// Commit the current project branch points at
+pub fn head() -> Result<String, GitError> {
+    Ok(git(&["rev-parse", "HEAD"])?.trim().to_string())
+}
+
+/// Fold every commit since `base`, plus anything staged, into one commit.
+/// Returns false when there was nothing to commit.
+pub fn squash(base: &str, message: &str) -> Result<bool, GitError> {
+    if head()? != base {
+        git(&["reset", "-q", "--soft", base])?;
+    }
+    if git(&["diff", "--cached", "--name-only"])?.trim().is_empty() {
+        return Ok(false);
+    }
+    git(&["commit", "-q", "-m", message])?;
+    Ok(true)
+}
+
+/// Throw away every commit and staged change since `base`
+pub fn rollback(base: &str) -> Result<(), GitError> {
+    git(&["reset", "-q", "--hard", base])?;
+    Ok(())
+}
+
+/// Read a file from a project branch without checking it out
+pub fn read_file(project: &str, path: &str) -> Result<String, GitError> {
+    git(&["show", &format!("{project}:{path}")])
+}
+
+/// Fail with ProjectNotFound unless the project branch exists
+pub fn ensure_project(project: &str) -> Result<(), GitError> {
+    let reference = format!("refs/heads/{project}");
+    match git(&["rev-parse", "--verify", "--quiet", &reference]) {
+        Ok(_) => Ok(()),
+        Err(GitError::Failed { .. }) => Err(GitError::ProjectNotFound(project.to_string())),
+        Err(err) => Err(err),
+    }
+}
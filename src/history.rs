@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use crate::ffi::{Priority, Status};
+use crate::git::{GitError, git};
+
+/// A single commit in the ticket repository
+pub struct Commit {
+    pub hash: String,
+    pub timestamp: i64,
+    pub author: String,
+    pub changes: Vec<Change>,
+}
+
+/// A file added or deleted inside a ticket directory
+pub struct Change {
+    pub added: bool,
+    pub ticket_id: String,
+    pub file: String,
+}
+
+/// Lifecycle of a single ticket reconstructed from the project history
+pub struct TicketHistory {
+    pub id: String,
+    pub created_at: i64,
+    pub created_by: String,
+    pub priority: Priority,
+    /// Status changes in chronological order, starting with the initial status
+    pub transitions: Vec<(i64, Status)>,
+    /// Set when the ticket directory no longer exists on the project
+    pub removed_at: Option<i64>,
+}
+
+impl TicketHistory {
+    /// Current status, or None if the ticket was removed. A ticket without
+    /// a status file counts as todo, as in `tix ls`.
+    pub fn status(&self) -> Option<Status> {
+        if self.removed_at.is_some() {
+            return None;
+        }
+        Some(self.transitions.last().map_or_else(Status::default, |(_, status)| *status))
+    }
+
+    /// Status the ticket had at the given time, or None if it didn't exist yet
+    pub fn status_at(&self, timestamp: i64) -> Option<Status> {
+        if timestamp < self.created_at || self.removed_at.is_some_and(|t| t <= timestamp) {
+            return None;
+        }
+        let status = self
+            .transitions
+            .iter()
+            .take_while(|(t, _)| *t <= timestamp)
+            .last()
+            .map_or_else(Status::default, |(_, status)| *status);
+        Some(status)
+    }
+
+    /// When the ticket last moved to done, if it is currently done
+    pub fn done_at(&self) -> Option<i64> {
+        match self.transitions.last() {
+            Some((t, Status::done)) if self.removed_at.is_none() => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// When the ticket first moved to doing
+    pub fn started_at(&self) -> Option<i64> {
+        self.transitions
+            .iter()
+            .find(|(_, status)| *status == Status::doing)
+            .map(|(t, _)| *t)
+    }
+
+    /// Seconds from creation to done
+    pub fn lead_time(&self) -> Option<i64> {
+        self.done_at().map(|done| done - self.created_at)
+    }
+
+    /// Seconds from first doing to done
+    pub fn cycle_time(&self) -> Option<i64> {
+        let done = self.done_at()?;
+        self.started_at()
+            .filter(|started| *started <= done)
+            .map(|started| done - started)
+    }
+}
+
+/// Read the full history of a project branch, oldest commit first
+pub fn commits(project: &str) -> Result<Vec<Commit>, GitError> {
+    let output = git(&[
+        "log",
+        "--reverse",
+        "--no-renames",
+        "--name-status",
+        "--format=%x1e%H%x09%at%x09%an",
+        project,
+        "--",
+    ])?;
+
+    let mut commits = Vec::new();
+    for entry in output.split('\x1e').filter(|e| !e.trim().is_empty()) {
+        let mut lines = entry.lines();
+        let mut header = lines.next().unwrap_or_default().splitn(3, '\t');
+        let (Some(hash), Some(timestamp), Some(author)) = (header.next(), header.next(), header.next())
+        else {
+            continue;
+        };
+
+        let changes = lines
+            .filter_map(|line| {
+                let (kind, path) = line.split_once('\t')?;
+                let (ticket_id, file) = path.split_once('/')?;
+                if !is_ticket_id(ticket_id) {
+                    return None;
+                }
+                Some(Change {
+                    added: kind != "D",
+                    ticket_id: ticket_id.to_string(),
+                    file: file.to_string(),
+                })
+            })
+            .collect();
+
+        commits.push(Commit {
+            hash: hash.to_string(),
+            timestamp: timestamp.parse().unwrap_or(0),
+            author: author.to_string(),
+            changes,
+        });
+    }
+
+    Ok(commits)
+}
+
+/// Reconstruct the lifecycle of every ticket that ever existed on a project
+pub fn tickets(project: &str) -> Result<Vec<TicketHistory>, GitError> {
+    Ok(reconstruct(&commits(project)?))
+}
+
+/// Replay commits in order to build per-ticket lifecycles
+pub fn reconstruct(commits: &[Commit]) -> Vec<TicketHistory> {
+    let mut tickets: Vec<TicketHistory> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for commit in commits {
+        for change in &commit.changes {
+            let i = *index.entry(change.ticket_id.clone()).or_insert_with(|| {
+                tickets.push(TicketHistory {
+                    id: change.ticket_id.clone(),
+                    created_at: commit.timestamp,
+                    created_by: commit.author.clone(),
+                    priority: Priority::z,
+                    transitions: Vec::new(),
+                    removed_at: None,
+                });
+                tickets.len() - 1
+            });
+            let ticket = &mut tickets[i];
+
+            match (change.added, change.file.as_str()) {
+                (true, "title.md") => ticket.removed_at = None,
+                (false, "title.md") => ticket.removed_at = Some(commit.timestamp),
+                (true, file) => {
+                    if let Some(s) = file.strip_prefix("s=") {
+                        let status = Status::from(s.as_bytes().first().copied().unwrap_or(0));
+                        ticket.transitions.push((commit.timestamp, status));
+                    } else if let Some(p) = file.strip_prefix("p=") {
+                        ticket.priority = Priority::from(p.as_bytes().first().copied().unwrap_or(0));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    tickets
+}
+
+/// Parse a window such as "12h", "30d" or "2w" into seconds
+pub fn parse_window(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    let split = value.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .ok()
+        .filter(|amount| *amount >= 0)
+        .ok_or_else(|| format!("invalid window '{value}', expected e.g. 30d or 2w"))?;
+
+    let unit = match unit {
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(format!("invalid window unit in '{value}', use h, d or w")),
+    };
+    amount
+        .checked_mul(unit)
+        .ok_or_else(|| format!("window '{value}' is too large"))
+}
+
+/// Current time as a unix timestamp
+pub fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+/// Format a unix timestamp as an RFC 3339 date-time
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Whether a directory name looks like a ticket ULID
+pub fn is_ticket_id(name: &str) -> bool {
+    name.len() == 26 && name.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+/// Crockford base32, the alphabet of ULIDs
+const ULID_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The 128 bits a ticket ULID encodes
+pub fn ticket_id_bits(id: &str) -> Option<u128> {
+    if !is_ticket_id(id) {
+        return None;
+    }
+    let mut bits: u128 = 0;
+    for byte in id.bytes() {
+        let digit = ULID_ALPHABET.iter().position(|c| *c == byte.to_ascii_uppercase())?;
+        // 26 digits hold 130 bits; the top two must be zero
+        if bits >> 123 != 0 {
+            return None;
+        }
+        bits = (bits << 5) | digit as u128;
+    }
+    Some(bits)
+}
+
+/// The ticket ULID for 128 bits, the inverse of `ticket_id_bits`
+pub fn ticket_id_from_bits(bits: u128) -> String {
+    (0..26)
+        .rev()
+        .map(|i| ULID_ALPHABET[((bits >> (i * 5)) & 31) as usize] as char)
+        .collect()
+}
+
+/// When a ticket was created, as a unix timestamp, from its ULID
+pub fn ticket_created(id: &str) -> Option<i64> {
+    ticket_id_bits(id).map(|bits| ((bits >> 80) / 1000) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(timestamp: i64, changes: &[(bool, &str)]) -> Commit {
+        Commit {
+            hash: timestamp.to_string(),
+            timestamp,
+            author: "ana".to_string(),
+            changes: changes
+                .iter()
+                .map(|(added, file)| Change {
+                    added: *added,
+                    ticket_id: "01J0ZQ4S9M8Y6VXKHRTB3C2DNA".to_string(),
+                    file: file.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn lead_and_cycle_time() {
+        let commits = [
+            commit(100, &[(true, "title.md"), (true, "s=t"), (true, "p=a")]),
+            commit(300, &[(false, "s=t"), (true, "s=w")]),
+            commit(1000, &[(false, "s=w"), (true, "s=d")]),
+        ];
+        let tickets = reconstruct(&commits);
+        assert_eq!(tickets.len(), 1);
+        let ticket = &tickets[0];
+        assert_eq!(ticket.priority, Priority::a);
+        assert_eq!(ticket.status_at(200), Some(Status::todo));
+        assert_eq!(ticket.lead_time(), Some(900));
+        assert_eq!(ticket.cycle_time(), Some(700));
+    }
+
+    #[test]
+    fn removed_ticket_is_not_done() {
+        let commits = [
+            commit(100, &[(true, "title.md"), (true, "s=d")]),
+            commit(200, &[(false, "title.md"), (false, "s=d")]),
+        ];
+        let ticket = &reconstruct(&commits)[0];
+        assert_eq!(ticket.status(), None);
+        assert_eq!(ticket.done_at(), None);
+        assert_eq!(ticket.status_at(150), Some(Status::done));
+    }
+
+    #[test]
+    fn ticket_id_bits_round_trip() {
+        for id in ["00000000000000000000000000", "01J0ZQ4S9M8Y6VXKHRTB3C2DNA", "7ZZZZZZZZZZZZZZZZZZZZZZZZZ"] {
+            let bits = ticket_id_bits(id).unwrap();
+            assert_eq!(ticket_id_from_bits(bits), id);
+        }
+        for bits in [0, 1, u128::MAX, 0x0190_0000_0000_dead_beef_0000_0000_0001] {
+            assert_eq!(ticket_id_bits(&ticket_id_from_bits(bits)), Some(bits));
+        }
+    }
+
+    #[test]
+    fn ticket_id_bits_accepts_lowercase() {
+        assert_eq!(
+            ticket_id_bits("01j0zq4s9m8y6vxkhrtb3c2dna"),
+            ticket_id_bits("01J0ZQ4S9M8Y6VXKHRTB3C2DNA")
+        );
+    }
+
+    #[test]
+    fn ticket_id_bits_rejects_invalid() {
+        // More than 128 bits
+        assert_eq!(ticket_id_bits("80000000000000000000000000"), None);
+        // I, L, O and U are not in the alphabet
+        assert_eq!(ticket_id_bits("0000000000000000000000000U"), None);
+        assert_eq!(ticket_id_bits("0000000000000000000000000"), None);
+        assert_eq!(ticket_id_bits("."), None);
+    }
+
+    #[test]
+    fn ticket_created_from_id() {
+        let millis: u128 = 1_718_000_000_123;
+        let id = ticket_id_from_bits(millis << 80 | 42);
+        assert_eq!(ticket_created(&id), Some(1_718_000_000));
+    }
+
+    #[test]
+    fn missing_status_counts_as_todo() {
+        let ticket = TicketHistory {
+            id: "01J0ZQ4S9M8Y6VXKHRTB3C2DNA".to_string(),
+            created_at: 100,
+            created_by: "a".to_string(),
+            priority: Priority::z,
+            transitions: Vec::new(),
+            removed_at: None,
+        };
+        assert_eq!(ticket.status(), Some(Status::todo));
+        assert_eq!(ticket.status_at(99), None);
+        assert_eq!(ticket.status_at(100), Some(Status::todo));
+    }
+
+    #[test]
+    fn parse_window_units_and_limits() {
+        assert_eq!(parse_window("12h"), Ok(12 * 3600));
+        assert_eq!(parse_window("2w"), Ok(14 * 86400));
+        assert!(parse_window("-1d").is_err());
+        assert!(parse_window("3y").is_err());
+        assert!(parse_window("999999999999999999w").unwrap_err().contains("too large"));
+    }
+}
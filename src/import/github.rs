@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::attrs::Extras;
+use crate::ffi::{Priority, Status};
+use crate::import::{self, ImportError, Record};
+
+/// Attribute that finds an issue's ticket again on re-import
+pub const KEY: &str = "github_url";
+
+/// An issue as exported by `gh issue list --json` or the REST API
+#[derive(Deserialize)]
+struct Issue {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    html_url: Option<String>,
+    /// Set on pull requests listed by the REST issues endpoint
+    #[serde(default)]
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Label {
+    Object { name: String },
+    Name(String),
+}
+
+impl Label {
+    fn name(&self) -> &str {
+        match self {
+            Label::Object { name } | Label::Name(name) => name,
+        }
+    }
+}
+
+/// How issue states and labels translate to tix, read from a TOML file:
+///
+/// ```toml
+/// [state]     # issue state -> status
+/// open = "todo"
+/// [status]    # label -> status, for open issues
+/// "in progress" = "doing"
+/// [priority]  # label -> priority
+/// P0 = "a"
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMapping {
+    #[serde(default)]
+    state: HashMap<String, String>,
+    #[serde(default)]
+    status: HashMap<String, String>,
+    #[serde(default)]
+    priority: HashMap<String, String>,
+}
+
+struct Mapping {
+    state: HashMap<String, Status>,
+    status: HashMap<String, Status>,
+    priority: HashMap<String, Priority>,
+}
+
+impl Mapping {
+    fn read(path: Option<&Path>) -> Result<Mapping, ImportError> {
+        let raw: RawMapping = import::read_mapping(path)?;
+        let mut state = import::parse_table(path, raw.state)?;
+        state.entry("open".to_string()).or_insert(Status::backlog);
+        state.entry("closed".to_string()).or_insert(Status::done);
+        Ok(Mapping {
+            state,
+            status: import::parse_table(path, raw.status)?,
+            priority: import::parse_table(path, raw.priority)?,
+        })
+    }
+}
+
+/// The value of the first label the table maps
+fn lookup<T: Copy>(table: &HashMap<String, T>, labels: &[String]) -> Option<T> {
+    labels.iter().find_map(|label| table.get(&label.to_lowercase()).copied())
+}
+
+/// Read the issues of a GitHub JSON export, skipping pull requests
+pub fn read(path: &Path, mapping: Option<&Path>) -> Result<Vec<Record>, ImportError> {
+    let mapping = Mapping::read(mapping)?;
+    let text = std::fs::read_to_string(path).map_err(|e| ImportError::Read(path.to_path_buf(), e))?;
+    let issues: Vec<Issue> =
+        serde_json::from_str(&text).map_err(|e| ImportError::Invalid(path.to_path_buf(), e.to_string()))?;
+
+    let mut records = Vec::with_capacity(issues.len());
+    for issue in issues.into_iter().filter(|issue| issue.pull_request.is_none()) {
+        let Some(url) = issue.html_url.or(issue.url) else {
+            return Err(ImportError::Invalid(path.to_path_buf(), format!("issue #{} has no url", issue.number)));
+        };
+        let labels: Vec<String> = issue.labels.iter().map(|l| l.name().to_string()).collect();
+
+        let state = issue.state.to_lowercase();
+        let status = match mapping.state.get(&state) {
+            Some(status) if state == "closed" => *status,
+            Some(status) => lookup(&mapping.status, &labels).unwrap_or(*status),
+            None => {
+                return Err(ImportError::Invalid(
+                    path.to_path_buf(),
+                    format!("issue #{} has unknown state '{}'", issue.number, issue.state),
+                ));
+            }
+        };
+
+        let mut extras = Extras {
+            labels: labels.iter().cloned().collect(),
+            ..Extras::default()
+        };
+        extras.attributes.insert("github_number".to_string(), issue.number.to_string());
+        extras.attributes.insert(KEY.to_string(), url);
+
+        records.push(Record {
+            name: format!("#{}", issue.number),
+            project: None,
+            id: None,
+            title: issue.title,
+            body: issue.body.filter(|body| !body.trim().is_empty()),
+            priority: lookup(&mapping.priority, &labels).unwrap_or(Priority::z),
+            status,
+            extras,
+        });
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_str(json: &str, mapping: Option<&str>) -> Result<Vec<Record>, ImportError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("issues.json");
+        std::fs::write(&path, json).unwrap();
+        let mapping_path = dir.path().join("github.toml");
+        if let Some(mapping) = mapping {
+            std::fs::write(&mapping_path, mapping).unwrap();
+        }
+        read(&path, mapping.map(|_| mapping_path.as_path()))
+    }
+
+    const ISSUES: &str = r#"[
+        {"number": 1, "title": "Fix login", "body": "", "state": "OPEN",
+         "labels": [{"name": "bug"}, {"name": "P0"}], "url": "https://github.com/o/r/issues/1"},
+        {"number": 2, "title": "Docs", "body": "Write them", "state": "closed",
+         "labels": ["In Progress"], "html_url": "https://github.com/o/r/issues/2",
+         "url": "https://api.github.com/repos/o/r/issues/2"},
+        {"number": 3, "title": "A pull request", "state": "open", "url": "https://github.com/o/r/pull/3",
+         "pull_request": {}}
+    ]"#;
+
+    #[test]
+    fn default_states_and_keys() {
+        let records = read_str(ISSUES, None).unwrap();
+        assert_eq!(records.len(), 2, "pull requests are skipped");
+
+        let login = &records[0];
+        assert_eq!(login.name, "#1");
+        assert_eq!(login.status, Status::backlog);
+        assert_eq!(login.priority, Priority::z);
+        assert_eq!(login.body, None);
+        let labels: Vec<&str> = login.extras.labels.iter().map(String::as_str).collect();
+        assert_eq!(labels, ["P0", "bug"]);
+        assert_eq!(login.extras.attributes[KEY], "https://github.com/o/r/issues/1");
+        assert_eq!(login.extras.attributes["github_number"], "1");
+
+        // The web URL wins, so exports from gh and the REST API find the same ticket
+        let docs = &records[1];
+        assert_eq!(docs.status, Status::done);
+        assert_eq!(docs.extras.attributes[KEY], "https://github.com/o/r/issues/2");
+    }
+
+    #[test]
+    fn mapped_labels() {
+        let mapping = "[state]\nopen = \"todo\"\n[status]\n\"in progress\" = \"doing\"\nbug = \"todo\"\n[priority]\np0 = \"a\"";
+        let records = read_str(ISSUES, Some(mapping)).unwrap();
+        assert_eq!(records[0].status, Status::todo);
+        assert_eq!(records[0].priority, Priority::a);
+        // Status labels only apply to open issues
+        assert_eq!(records[1].status, Status::done);
+
+        let open = r#"[{"number": 4, "title": "Wip", "state": "open", "labels": ["In Progress"], "url": "u"}]"#;
+        assert_eq!(read_str(open, Some(mapping)).unwrap()[0].status, Status::doing);
+    }
+
+    #[test]
+    fn invalid_issues() {
+        let unknown = r#"[{"number": 1, "title": "x", "state": "draft", "url": "u"}]"#;
+        assert!(matches!(read_str(unknown, None), Err(ImportError::Invalid(..))));
+        let no_url = r#"[{"number": 1, "title": "x", "state": "open"}]"#;
+        assert!(matches!(read_str(no_url, None), Err(ImportError::Invalid(..))));
+        assert!(matches!(read_str(ISSUES, Some("[status]\nbug = \"later\"")), Err(ImportError::Invalid(..))));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::attrs::Extras;
+use crate::ffi::{Priority, Status};
+use crate::import::{self, ImportError, Record, Unmapped, wiki};
+
+/// Attribute that finds an issue's ticket again on re-import
+pub const KEY: &str = "jira_key";
+
+/// CSV columns each ticket field comes from
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Columns {
+    title: String,
+    body: String,
+    status: String,
+    priority: String,
+    key: String,
+    /// Jira repeats this column once per label
+    labels: String,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Columns {
+            title: "Summary".to_string(),
+            body: "Description".to_string(),
+            status: "Status".to_string(),
+            priority: "Priority".to_string(),
+            key: "Issue key".to_string(),
+            labels: "Labels".to_string(),
+        }
+    }
+}
+
+/// Column names and value tables, read from a TOML file:
+///
+/// ```toml
+/// [columns]
+/// title = "Summary"
+/// [status]
+/// "Code Review" = "doing"
+/// [priority]
+/// Blocker = "a"
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMapping {
+    #[serde(default)]
+    columns: Columns,
+    #[serde(default)]
+    status: HashMap<String, String>,
+    #[serde(default)]
+    priority: HashMap<String, String>,
+}
+
+/// Jira's default workflow and priority scheme
+const STATUSES: &[(&str, Status)] = &[
+    ("backlog", Status::backlog),
+    ("open", Status::todo),
+    ("to do", Status::todo),
+    ("selected for development", Status::todo),
+    ("in progress", Status::doing),
+    ("in review", Status::doing),
+    ("done", Status::done),
+    ("closed", Status::done),
+    ("resolved", Status::done),
+];
+
+const PRIORITIES: &[(&str, Priority)] = &[
+    ("highest", Priority::a),
+    ("high", Priority::a),
+    ("medium", Priority::b),
+    ("low", Priority::c),
+    ("lowest", Priority::c),
+];
+
+/// Position of each mapped column in the CSV header
+struct Header {
+    title: usize,
+    key: usize,
+    body: Option<usize>,
+    status: Option<usize>,
+    priority: Option<usize>,
+    labels: Vec<usize>,
+}
+
+impl Header {
+    fn find(path: &Path, header: &csv::StringRecord, columns: &Columns) -> Result<Header, ImportError> {
+        let position = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+        let required = |name: &str| {
+            position(name).ok_or_else(|| ImportError::Invalid(path.to_path_buf(), format!("no '{name}' column")))
+        };
+        Ok(Header {
+            title: required(&columns.title)?,
+            key: required(&columns.key)?,
+            body: position(&columns.body),
+            status: position(&columns.status),
+            priority: position(&columns.priority),
+            labels: header
+                .iter()
+                .enumerate()
+                .filter(|(_, h)| h.trim().eq_ignore_ascii_case(&columns.labels))
+                .map(|(i, _)| i)
+                .collect(),
+        })
+    }
+}
+
+/// Look a value up, counting it as unmapped when it isn't empty
+fn map<T: Copy>(unmapped: &mut Unmapped, field: &'static str, value: &str, table: &HashMap<String, T>) -> Option<T> {
+    let found = table.get(&value.to_lowercase()).copied();
+    if found.is_none() && !value.is_empty() {
+        *unmapped.entry((field, value.to_string())).or_insert(0) += 1;
+    }
+    found
+}
+
+/// Read the issues of a Jira CSV export. Statuses and priorities missing
+/// from the mapping are counted in the returned report; their tickets
+/// fall back to backlog and z.
+pub fn read(path: &Path, mapping: Option<&Path>) -> Result<(Vec<Record>, Unmapped), ImportError> {
+    let raw: RawMapping = import::read_mapping(mapping)?;
+    let mut statuses: HashMap<String, Status> = STATUSES.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    statuses.extend(import::parse_table(mapping, raw.status)?);
+    let mut priorities: HashMap<String, Priority> = PRIORITIES.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    priorities.extend(import::parse_table(mapping, raw.priority)?);
+
+    let invalid = |e: csv::Error| ImportError::Invalid(path.to_path_buf(), e.to_string());
+    let file = std::fs::File::open(path).map_err(|e| ImportError::Read(path.to_path_buf(), e))?;
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(file);
+    let header = Header::find(path, reader.headers().map_err(invalid)?, &raw.columns)?;
+
+    let mut records = Vec::new();
+    let mut unmapped = Unmapped::new();
+    for (row, result) in reader.records().enumerate() {
+        let row_data = result.map_err(invalid)?;
+        let cell = |i: Option<usize>| i.and_then(|i| row_data.get(i)).map(str::trim).unwrap_or("");
+
+        let key = cell(Some(header.key));
+        let title = cell(Some(header.title));
+        if key.is_empty() || title.is_empty() {
+            return Err(ImportError::Invalid(
+                path.to_path_buf(),
+                format!("row {} has no {} or {}", row + 2, raw.columns.key, raw.columns.title),
+            ));
+        }
+
+        let status = map(&mut unmapped, "status", cell(header.status), &statuses).unwrap_or(Status::backlog);
+        let priority = map(&mut unmapped, "priority", cell(header.priority), &priorities).unwrap_or(Priority::z);
+
+        let mut extras = Extras {
+            labels: header
+                .labels
+                .iter()
+                .map(|i| cell(Some(*i)))
+                .filter(|label| !label.is_empty())
+                .map(str::to_string)
+                .collect(),
+            ..Extras::default()
+        };
+        extras.attributes.insert(KEY.to_string(), key.to_string());
+
+        let body = wiki::to_markdown(cell(header.body));
+        records.push(Record {
+            name: key.to_string(),
+            project: None,
+            id: None,
+            title: title.to_string(),
+            body: (!body.is_empty()).then_some(body),
+            priority,
+            status,
+            extras,
+        });
+    }
+    Ok((records, unmapped))
+}
@@ -0,0 +1,381 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+
+use crate::attrs::{self, Extras};
+use crate::branch;
+use crate::ffi::add::AddError;
+use crate::ffi::amend::AmendError;
+use crate::ffi::mv::MvError;
+use crate::ffi::project::SwitchError;
+use crate::ffi::{self, Priority, Status, Ticket, TixError};
+use crate::git::{self, GitError};
+
+pub mod github;
+pub mod jira;
+pub mod taskwarrior;
+pub mod todotxt;
+pub mod trello;
+pub mod wiki;
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error(transparent)]
+    TixError(#[from] TixError),
+
+    #[error(transparent)]
+    GitError(#[from] GitError),
+
+    #[error(transparent)]
+    AddError(#[from] AddError),
+
+    #[error(transparent)]
+    AmendError(#[from] AmendError),
+
+    #[error(transparent)]
+    MvError(#[from] MvError),
+
+    #[error(transparent)]
+    SwitchError(#[from] SwitchError),
+
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Invalid {0}: {1}")]
+    Invalid(PathBuf, String),
+
+    #[error("{0} values have no mapping, add them to the mapping file")]
+    Unmapped(usize),
+
+    #[error("{0} appears more than once in the import")]
+    Duplicate(String),
+
+    #[error("Failed to write ticket {0}: {1}")]
+    Write(String, std::io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Save(PathBuf, std::io::Error),
+
+    #[error("Failed to read answer: {0}")]
+    Prompt(std::io::Error),
+
+    #[error("Failed to import {0}, no tickets were changed: {1}")]
+    RolledBack(String, #[source] Box<ImportError>),
+}
+
+/// Source values without a mapping, as (field, value), and how many
+/// records use each
+pub type Unmapped = BTreeMap<(&'static str, String), usize>;
+
+/// Read a TOML mapping file, or the defaults when there is none
+pub fn read_mapping<T: DeserializeOwned + Default>(path: Option<&Path>) -> Result<T, ImportError> {
+    let Some(path) = path else {
+        return Ok(T::default());
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| ImportError::Read(path.to_path_buf(), e))?;
+    toml::from_str(&text).map_err(|e| ImportError::Invalid(path.to_path_buf(), e.to_string()))
+}
+
+/// Parse the values of a mapping table, keyed by lowercase source value
+pub fn parse_table<T: ValueEnum>(
+    path: Option<&Path>,
+    table: HashMap<String, String>,
+) -> Result<HashMap<String, T>, ImportError> {
+    table
+        .into_iter()
+        .map(|(key, value)| match T::from_str(&value, true) {
+            Ok(parsed) => Ok((key.to_lowercase(), parsed)),
+            Err(e) => Err(ImportError::Invalid(
+                path.unwrap_or(Path::new("mapping")).to_path_buf(),
+                format!("{key} = \"{value}\": {e}"),
+            )),
+        })
+        .collect()
+}
+
+/// A ticket as read from another tracker
+pub struct Record {
+    /// How the record is called in messages, e.g. "#12"
+    pub name: String,
+    /// Project to import into instead of the current one
+    pub project: Option<String>,
+    /// Ticket the source already knows the record as, e.g. from an export
+    pub id: Option<String>,
+    pub title: String,
+    pub body: Option<String>,
+    pub priority: Priority,
+    pub status: Status,
+    /// Labels, plus attributes that include the source's key
+    pub extras: Extras,
+}
+
+/// What importing a record does to its project
+pub enum Action {
+    Create,
+    /// The existing ticket, its labels and attributes after the import,
+    /// and a description of each change
+    Update(Ticket, Extras, Vec<String>),
+    Unchanged(Ticket),
+}
+
+pub struct Step {
+    pub record: Record,
+    pub action: Action,
+}
+
+/// The steps of an import that land in one project
+pub struct Batch {
+    pub project: String,
+    /// Whether the project branch has to be created
+    pub create: bool,
+    pub steps: Vec<Step>,
+}
+
+/// Group records by project, the current one first, and work out what
+/// each one changes. Projects are read without switching to them.
+pub fn plan(records: Vec<Record>, key: &str) -> Result<Vec<Batch>, ImportError> {
+    let current = git::current_branch()?;
+    let mut groups: Vec<(String, Vec<Record>)> = vec![(current.clone(), Vec::new())];
+    for record in records {
+        let project = record.project.clone().unwrap_or_else(|| current.clone());
+        match groups.iter_mut().find(|(name, _)| *name == project) {
+            Some((_, group)) => group.push(record),
+            None => groups.push((project, vec![record])),
+        }
+    }
+
+    let mut batches = Vec::new();
+    for (project, records) in groups.into_iter().filter(|(_, records)| !records.is_empty()) {
+        let create = match git::ensure_project(&project) {
+            Ok(()) => false,
+            Err(GitError::ProjectNotFound(_)) => true,
+            Err(err) => return Err(err.into()),
+        };
+        let steps = plan_project(&project, create, records, key)?;
+        batches.push(Batch { project, create, steps });
+    }
+    Ok(batches)
+}
+
+/// Match records to tickets of a project by ticket ID, then by the `key`
+/// attribute
+fn plan_project(project: &str, create: bool, records: Vec<Record>, key: &str) -> Result<Vec<Step>, ImportError> {
+    let (tickets, mut extras) = if create {
+        Default::default()
+    } else {
+        (branch::tickets(project)?, attrs::read(project)?)
+    };
+    let mut by_id: HashMap<String, Ticket> = tickets.into_iter().map(|t| (t.id.clone(), t)).collect();
+    let by_key: HashMap<String, String> = extras
+        .iter()
+        .filter_map(|(id, e)| Some((e.attributes.get(key)?.clone(), id.clone())))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut steps = Vec::with_capacity(records.len());
+    for record in records {
+        let value = record.extras.attributes.get(key);
+        if let Some(value) = value
+            && !seen.insert(value.clone())
+        {
+            return Err(ImportError::Duplicate(record.name));
+        }
+
+        let ticket = record
+            .id
+            .as_ref()
+            .and_then(|id| by_id.remove(id))
+            .or_else(|| by_id.remove(by_key.get(value?)?));
+        let Some(ticket) = ticket else {
+            steps.push(Step { record, action: Action::Create });
+            continue;
+        };
+        let mut merged = extras.remove(&ticket.id).unwrap_or_default();
+        let changes = changes(&ticket, &merged, &record);
+        merged.labels = record.extras.labels.clone();
+        merged.attributes.extend(record.extras.attributes.clone());
+
+        let action = if changes.is_empty() {
+            Action::Unchanged(ticket)
+        } else {
+            Action::Update(ticket, merged, changes)
+        };
+        steps.push(Step { record, action });
+    }
+    Ok(steps)
+}
+
+fn changes(ticket: &Ticket, extras: &Extras, record: &Record) -> Vec<String> {
+    let mut changes = Vec::new();
+    if ticket.title != record.title.trim_end() {
+        changes.push("title".to_string());
+    }
+    if body_changed(ticket, record) {
+        changes.push("body".to_string());
+    }
+    if ticket.priority != record.priority {
+        changes.push(format!("priority {:?} -> {:?}", ticket.priority, record.priority));
+    }
+    if ticket.status != record.status {
+        changes.push(format!("status {:?} -> {:?}", ticket.status, record.status));
+    }
+    if extras.labels != record.extras.labels {
+        changes.push("labels".to_string());
+    }
+    if record
+        .extras
+        .attributes
+        .iter()
+        .any(|(key, value)| extras.attributes.get(key) != Some(value))
+    {
+        changes.push("attributes".to_string());
+    }
+    changes
+}
+
+/// A body can be replaced but not cleared through the library
+fn body_changed(ticket: &Ticket, record: &Record) -> bool {
+    let body = record.body.as_deref().unwrap_or("").trim();
+    !body.is_empty() && ticket.body.as_deref().unwrap_or("").trim() != body
+}
+
+/// Carry out an import, switching to each batch's project and back.
+/// Returns the ticket ID of every step, batch by batch. If any project
+/// fails, the projects imported before it are put back as well.
+pub fn apply(batches: &[Batch], source: &str) -> Result<Vec<Vec<String>>, ImportError> {
+    let current = git::current_branch()?;
+    let mut ids = Vec::with_capacity(batches.len());
+    // Where each project touched so far started, None if the import created it
+    let mut started: Vec<(&str, Option<String>)> = Vec::new();
+
+    let result = batches.iter().try_for_each(|batch| {
+        let head = match batch.create {
+            true => None,
+            false => Some(git::git(&["rev-parse", &format!("refs/heads/{}", batch.project)])?.trim().to_string()),
+        };
+        started.push((&batch.project, head));
+        if batch.project != current {
+            ffi::switch(&batch.project, batch.create)?;
+        }
+        ids.push(apply_project(&batch.steps, source)?);
+        Ok(())
+    });
+    if git::current_branch()? != current {
+        ffi::switch(&current, false)?;
+    }
+    if result.is_err() {
+        restore(&current, &started)?;
+    }
+    result.map(|()| ids)
+}
+
+/// Put every project back where it started, from the current project
+fn restore(current: &str, started: &[(&str, Option<String>)]) -> Result<(), GitError> {
+    for (project, head) in started {
+        match head {
+            Some(head) if *project == current => git::rollback(head)?,
+            Some(head) => {
+                git::git(&["update-ref", &format!("refs/heads/{project}"), head])?;
+            }
+            None if git::ensure_project(project).is_ok() => {
+                git::git(&["branch", "-D", project])?;
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Apply the steps of one project through the tix library, squashed into
+/// a single commit so one undo reverts them. Any failure resets the
+/// project to where it started.
+fn apply_project(steps: &[Step], source: &str) -> Result<Vec<String>, ImportError> {
+    let base = git::head()?;
+    let mut ids = Vec::with_capacity(steps.len());
+
+    for step in steps {
+        match apply_step(step) {
+            Ok(id) => ids.push(id),
+            Err(err) => {
+                git::rollback(&base)?;
+                return Err(ImportError::RolledBack(step.record.name.clone(), Box::new(err)));
+            }
+        }
+    }
+
+    let count = |f: fn(&Action) -> bool| steps.iter().filter(|s| f(&s.action)).count();
+    let message = format!(
+        "import from {source}: {} created, {} updated",
+        count(|a| matches!(a, Action::Create)),
+        count(|a| matches!(a, Action::Update(..))),
+    );
+    git::squash(&base, &message)?;
+    Ok(ids)
+}
+
+fn apply_step(step: &Step) -> Result<String, ImportError> {
+    let record = &step.record;
+    let (id, extras) = match &step.action {
+        Action::Unchanged(ticket) => return Ok(ticket.id.clone()),
+        Action::Create => {
+            let id = ffi::add(&record.title, record.body.as_deref(), record.priority, Some(record.status))?;
+            (id, &record.extras)
+        }
+        Action::Update(ticket, extras, _) => {
+            let title = (ticket.title != record.title.trim_end()).then_some(record.title.as_str());
+            let body = record.body.as_deref().filter(|_| body_changed(ticket, record));
+            let priority = (ticket.priority != record.priority).then_some(record.priority);
+            if title.is_some() || body.is_some() || priority.is_some() {
+                ffi::amend(&ticket.id, title, body, priority)?;
+            }
+            if ticket.status != record.status {
+                ffi::mv(&ticket.id, record.status)?;
+            }
+            (ticket.id.clone(), extras)
+        }
+    };
+
+    attrs::write(&git::tix_dir().join(&id), extras).map_err(|err| ImportError::Write(id.clone(), err))?;
+    git::git(&["add", "-A", "--", &id])?;
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(status: Status, body: Option<&str>) -> Record {
+        let mut extras = Extras::default();
+        extras.labels.insert("bug".to_string());
+        extras.attributes.insert("github_url".to_string(), "u".to_string());
+        Record {
+            name: "#1".to_string(),
+            project: None,
+            id: None,
+            title: "Fix login".to_string(),
+            body: body.map(str::to_string),
+            priority: Priority::a,
+            status,
+            extras,
+        }
+    }
+
+    #[test]
+    fn reimport_without_changes() {
+        let ticket = Ticket {
+            id: "01J0ZQ4S9M8Y6VXKHRTB3C2DNA".to_string(),
+            title: "Fix login".to_string(),
+            body: Some("Steps".to_string()),
+            priority: Priority::a,
+            status: Status::todo,
+        };
+        let extras = record(Status::todo, None).extras;
+        assert!(changes(&ticket, &extras, &record(Status::todo, None)).is_empty());
+        assert!(changes(&ticket, &extras, &record(Status::todo, Some("Steps\n"))).is_empty());
+        assert_eq!(changes(&ticket, &extras, &record(Status::done, None)), ["status todo -> done"]);
+        assert_eq!(changes(&ticket, &Extras::default(), &record(Status::todo, None)), ["labels", "attributes"]);
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::attrs::Extras;
+use crate::ffi::{Priority, Status};
+use crate::history;
+use crate::import::{Batch, ImportError, Record};
+
+/// Attribute that finds a task's ticket again on re-import
+pub const KEY: &str = "taskwarrior_uuid";
+
+/// Taskwarrior's date format, e.g. 20240105T120000Z
+pub const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A task as printed by `task export`
+#[derive(Deserialize)]
+struct Task {
+    uuid: String,
+    description: String,
+    status: String,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    start: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+#[derive(Deserialize)]
+struct Annotation {
+    entry: String,
+    description: String,
+}
+
+/// Task UUIDs and the tickets they were imported as, kept outside the
+/// ticket repository so repeated syncs find the same tickets
+pub struct UuidMap {
+    path: PathBuf,
+    pub tickets: BTreeMap<String, String>,
+}
+
+impl UuidMap {
+    /// The map in .tix/.git next to the workspace config, unless `path` is given
+    pub fn default_path() -> PathBuf {
+        crate::git::tix_dir().join(".git").join("taskwarrior.json")
+    }
+
+    pub fn read(path: PathBuf) -> Result<UuidMap, ImportError> {
+        let tickets = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| ImportError::Invalid(path.clone(), e.to_string()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(ImportError::Read(path, e)),
+        };
+        Ok(UuidMap { path, tickets })
+    }
+
+    /// Remember the ticket every imported task ended up as
+    pub fn update(&mut self, batches: &[Batch], ids: &[Vec<String>]) -> Result<(), ImportError> {
+        for (batch, ids) in batches.iter().zip(ids) {
+            for (step, id) in batch.steps.iter().zip(ids) {
+                if let Some(uuid) = step.record.extras.attributes.get(KEY) {
+                    self.tickets.insert(uuid.clone(), id.clone());
+                }
+            }
+        }
+        let text = serde_json::to_string_pretty(&self.tickets).expect("a string map serializes");
+        std::fs::write(&self.path, text + "\n").map_err(|e| ImportError::Save(self.path.clone(), e))
+    }
+
+    /// The UUID a ticket is known by: the task it was imported from, or
+    /// the ticket's own ULID read as a UUID
+    pub fn uuid(&self, ticket_id: &str) -> Option<String> {
+        self.tickets
+            .iter()
+            .find(|(_, id)| *id == ticket_id)
+            .map(|(uuid, _)| uuid.clone())
+            .or_else(|| history::ticket_id_bits(ticket_id).map(format_uuid))
+    }
+
+    /// The ticket a task UUID belongs to, if tix has seen it
+    fn ticket(&self, uuid: &str) -> Option<String> {
+        self.tickets.get(uuid).cloned().or_else(|| {
+            // A task exported by tix carries the ticket ULID as its UUID
+            let bits = u128::from_str_radix(&uuid.replace('-', ""), 16).ok()?;
+            Some(history::ticket_id_from_bits(bits))
+        })
+    }
+}
+
+fn format_uuid(bits: u128) -> String {
+    let hex = format!("{bits:032x}");
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Taskwarrior date to RFC 3339, or the input if it isn't one
+pub fn from_task_date(date: &str) -> String {
+    chrono::NaiveDateTime::parse_from_str(date, DATE_FORMAT)
+        .map(|dt| dt.and_utc().to_rfc3339())
+        .unwrap_or_else(|_| date.to_string())
+}
+
+/// Read a `task export` JSON file. Deleted tasks and recurring templates
+/// are skipped; annotations become the body, one list item each.
+pub fn read(path: &Path, map: &UuidMap) -> Result<Vec<Record>, ImportError> {
+    let text = std::fs::read_to_string(path).map_err(|e| ImportError::Read(path.to_path_buf(), e))?;
+    let tasks: Vec<Task> =
+        serde_json::from_str(&text).map_err(|e| ImportError::Invalid(path.to_path_buf(), e.to_string()))?;
+
+    let mut records = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let status = match task.status.as_str() {
+            "pending" if task.start.is_some() => Status::doing,
+            "pending" => Status::todo,
+            "waiting" => Status::backlog,
+            "completed" => Status::done,
+            _ => continue,
+        };
+        let priority = match task.priority.as_deref() {
+            Some("H") => Priority::a,
+            Some("M") => Priority::b,
+            Some("L") => Priority::c,
+            _ => Priority::z,
+        };
+
+        let mut extras = Extras {
+            labels: task.tags.into_iter().collect(),
+            ..Extras::default()
+        };
+        extras.attributes.insert(KEY.to_string(), task.uuid.clone());
+        if let Some(due) = task.due {
+            extras.attributes.insert("due".to_string(), from_task_date(&due));
+        }
+        if let Some(project) = task.project {
+            extras.attributes.insert("taskwarrior_project".to_string(), project);
+        }
+
+        let body: Vec<String> = task
+            .annotations
+            .iter()
+            .map(|a| format!("- {}: {}", from_task_date(&a.entry).get(..10).unwrap_or(&a.entry), a.description))
+            .collect();
+        records.push(Record {
+            name: task.uuid.get(..8).unwrap_or(&task.uuid).to_string(),
+            project: None,
+            id: map.ticket(&task.uuid),
+            title: task.description,
+            body: (!body.is_empty()).then(|| body.join("\n")),
+            priority,
+            status,
+            extras,
+        });
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{Action, Step};
+
+    const TASKS: &str = r#"[
+        {"uuid": "5f1a3c9e-0000-4000-8000-000000000001", "description": "Fix login", "status": "pending",
+         "priority": "H", "tags": ["bug", "web"], "due": "20240105T120000Z", "project": "portal",
+         "annotations": [{"entry": "20240102T080000Z", "description": "Seen on staging"}]},
+        {"uuid": "5f1a3c9e-0000-4000-8000-000000000002", "description": "Review", "status": "pending",
+         "start": "20240103T090000Z", "priority": "L"},
+        {"uuid": "5f1a3c9e-0000-4000-8000-000000000003", "description": "Someday", "status": "waiting"},
+        {"uuid": "5f1a3c9e-0000-4000-8000-000000000004", "description": "Shipped", "status": "completed"},
+        {"uuid": "5f1a3c9e-0000-4000-8000-000000000005", "description": "Gone", "status": "deleted"},
+        {"uuid": "5f1a3c9e-0000-4000-8000-000000000006", "description": "Weekly", "status": "recurring"}
+    ]"#;
+
+    fn read_str(json: &str, map: &UuidMap) -> Vec<Record> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+        std::fs::write(&path, json).unwrap();
+        read(&path, map).unwrap()
+    }
+
+    fn empty_map(dir: &Path) -> UuidMap {
+        UuidMap::read(dir.join("taskwarrior.json")).unwrap()
+    }
+
+    #[test]
+    fn statuses_priorities_and_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let records = read_str(TASKS, &empty_map(dir.path()));
+        let summary: Vec<(&str, Status, Priority)> =
+            records.iter().map(|r| (r.title.as_str(), r.status, r.priority)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("Fix login", Status::todo, Priority::a),
+                ("Review", Status::doing, Priority::c),
+                ("Someday", Status::backlog, Priority::z),
+                ("Shipped", Status::done, Priority::z),
+            ]
+        );
+
+        let login = &records[0];
+        assert_eq!(login.name, "5f1a3c9e");
+        assert_eq!(login.body.as_deref(), Some("- 2024-01-02: Seen on staging"));
+        assert_eq!(login.extras.labels.len(), 2);
+        assert_eq!(login.extras.attributes[KEY], "5f1a3c9e-0000-4000-8000-000000000001");
+        assert_eq!(login.extras.attributes["due"], "2024-01-05T12:00:00+00:00");
+        assert_eq!(login.extras.attributes["taskwarrior_project"], "portal");
+        assert_eq!(records[1].body, None);
+    }
+
+    #[test]
+    fn uuid_map_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut map = empty_map(dir.path());
+        assert!(map.tickets.is_empty());
+
+        // Tickets never imported are exported with their ULID as UUID and
+        // found again from it
+        let ulid = "01J0ZQ4S9M8Y6VXKHRTB3C2DNA";
+        let uuid = map.uuid(ulid).unwrap();
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(map.ticket(&uuid).as_deref(), Some(ulid));
+
+        let records = read_str(TASKS, &map);
+        let uuid = records[0].extras.attributes[KEY].clone();
+        let batch = Batch {
+            project: "main".to_string(),
+            create: false,
+            steps: records.into_iter().take(1).map(|record| Step { record, action: Action::Create }).collect(),
+        };
+        map.update(&[batch], &[vec![ulid.to_string()]]).unwrap();
+
+        let map = empty_map(dir.path());
+        assert_eq!(map.tickets.get(&uuid).map(String::as_str), Some(ulid));
+        assert_eq!(map.uuid(ulid), Some(uuid.clone()));
+        assert_eq!(read_str(TASKS, &map)[0].id.as_deref(), Some(ulid));
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::attrs::Extras;
+use crate::ffi::{Priority, Status};
+use crate::history::is_ticket_id;
+use crate::import::{ImportError, Record};
+
+/// Attribute that finds a task's ticket again on re-import, for tasks
+/// that don't carry a `tix:` ID yet
+pub const KEY: &str = "todotxt_task";
+
+/// Read a todo.txt file: `x` marks done, `(A)`-`(C)` set the priority,
+/// the first `+project` picks the tix project and `@context`s become
+/// labels. `tix:`, `pri:` and `status:` tags written by `tix export
+/// todotxt` are understood; other `key:value` tags stay in the title, as
+/// does any word escaped with a leading `\`.
+pub fn read(path: &Path) -> Result<Vec<Record>, ImportError> {
+    let text = std::fs::read_to_string(path).map_err(|e| ImportError::Read(path.to_path_buf(), e))?;
+    let mut records = Vec::new();
+    // Identical lines are told apart by how often the task came before
+    let mut occurrences: HashMap<(Option<String>, String), usize> = HashMap::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut record = parse(line, number + 1).ok_or_else(|| {
+            ImportError::Invalid(path.to_path_buf(), format!("line {} has no task text", number + 1))
+        })?;
+        if let Some(key) = record.extras.attributes.get_mut(KEY) {
+            let count = occurrences.entry((record.project.clone(), key.clone())).or_default();
+            *count += 1;
+            if *count > 1 {
+                *key = format!("{key} #{count}");
+            }
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Escape the words of a title that `read` would otherwise take for a
+/// project, context, tag, or a completion mark, priority or date
+pub fn escape(title: &str) -> String {
+    title
+        .split_whitespace()
+        .enumerate()
+        .map(|(i, word)| if is_markup(word, i == 0) { format!("\\{word}") } else { word.to_string() })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether a word means more than its text; `first` adds what only counts
+/// at the start of a task
+fn is_markup(word: &str, first: bool) -> bool {
+    let tagged = |prefix: char| word.len() > 1 && word.starts_with(prefix);
+    tagged('+')
+        || tagged('@')
+        || tagged('\\')
+        || ["tix:", "pri:", "status:"].iter().any(|tag| word.starts_with(tag))
+        || (first && (word == "x" || priority_token(word).is_some() || is_date(word)))
+}
+
+fn parse(line: &str, number: usize) -> Option<Record> {
+    let mut tokens = line.split_whitespace().peekable();
+    let done = tokens.next_if_eq(&"x").is_some();
+    let mut priority = tokens.next_if(|t| priority_token(t).is_some()).and_then(priority_token);
+    // Completion and creation dates
+    while tokens.next_if(|t| is_date(t)).is_some() {}
+
+    let mut status = None;
+    let mut project = None;
+    let mut id = None;
+    let mut extras = Extras::default();
+    let mut words = Vec::new();
+    for token in tokens {
+        if let Some(word) = token.strip_prefix('\\').filter(|word| is_markup(word, true)) {
+            words.push(word);
+        } else if let Some(name) = token.strip_prefix('+').filter(|n| !n.is_empty())
+            && project.is_none()
+        {
+            project = Some(name.to_string());
+        } else if let Some(context) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+            extras.labels.insert(context.to_string());
+        } else if let Some(ticket_id) = token.strip_prefix("tix:").filter(|id| is_ticket_id(id)) {
+            id = Some(ticket_id.to_string());
+        } else if let Some(letter) = token.strip_prefix("pri:").filter(|l| l.len() == 1) {
+            priority = priority_letter(letter.as_bytes()[0]);
+        } else if let Some(value) = token.strip_prefix("status:")
+            && let Ok(parsed) = <Status as clap::ValueEnum>::from_str(value, true)
+        {
+            status = Some(parsed);
+        } else {
+            words.push(token);
+        }
+    }
+    if words.is_empty() {
+        return None;
+    }
+
+    let title = words.join(" ");
+    if id.is_none() {
+        extras.attributes.insert(KEY.to_string(), title.clone());
+    }
+    Some(Record {
+        name: format!("line {number}"),
+        project,
+        id,
+        title,
+        body: None,
+        priority: priority.unwrap_or(Priority::z),
+        status: if done { Status::done } else { status.unwrap_or(Status::todo) },
+        extras,
+    })
+}
+
+/// `(A)` -> a; priorities below C have no tix equivalent
+fn priority_token(token: &str) -> Option<Priority> {
+    match token.as_bytes() {
+        [b'(', letter @ b'A'..=b'Z', b')'] => Some(priority_letter(*letter).unwrap_or(Priority::z)),
+        _ => None,
+    }
+}
+
+fn priority_letter(letter: u8) -> Option<Priority> {
+    match letter.to_ascii_uppercase() {
+        b'A' => Some(Priority::a),
+        b'B' => Some(Priority::b),
+        b'C' => Some(Priority::c),
+        _ => None,
+    }
+}
+
+fn is_date(token: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(token, "%Y-%m-%d").is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_task() {
+        let record = parse("Buy milk", 1).unwrap();
+        assert_eq!(record.title, "Buy milk");
+        assert_eq!(record.status, Status::todo);
+        assert_eq!(record.priority, Priority::z);
+        assert_eq!(record.project, None);
+        assert_eq!(record.name, "line 1");
+        assert_eq!(record.extras.attributes.get(KEY).map(String::as_str), Some("Buy milk"));
+    }
+
+    #[test]
+    fn done_priority_and_dates() {
+        let record = parse("x (A) 2024-06-02 2024-06-01 Ship it", 3).unwrap();
+        assert_eq!(record.title, "Ship it");
+        assert_eq!(record.status, Status::done);
+        assert_eq!(record.priority, Priority::a);
+
+        // Below C there is no tix priority
+        assert_eq!(parse("(D) Later", 1).unwrap().priority, Priority::z);
+        // A priority only counts at the start
+        assert_eq!(parse("Call (A) Bob", 1).unwrap().title, "Call (A) Bob");
+    }
+
+    #[test]
+    fn project_contexts_and_tags() {
+        let record = parse("Fix login +web @phone @office due:friday", 1).unwrap();
+        assert_eq!(record.title, "Fix login due:friday");
+        assert_eq!(record.project.as_deref(), Some("web"));
+        let labels: Vec<&str> = record.extras.labels.iter().map(String::as_str).collect();
+        assert_eq!(labels, ["office", "phone"]);
+    }
+
+    #[test]
+    fn tix_tags() {
+        let record = parse("Fix login status:doing pri:B tix:01J0000000000000000000000A", 1).unwrap();
+        assert_eq!(record.title, "Fix login");
+        assert_eq!(record.status, Status::doing);
+        assert_eq!(record.priority, Priority::b);
+        assert_eq!(record.id.as_deref(), Some("01J0000000000000000000000A"));
+        assert!(record.extras.attributes.is_empty());
+
+        // Tags that don't parse stay in the title
+        let record = parse("Fix status:later tix:nope", 1).unwrap();
+        assert_eq!(record.title, "Fix status:later tix:nope");
+        assert_eq!(record.id, None);
+    }
+
+    #[test]
+    fn escaped_titles_round_trip() {
+        let titles = [
+            "Fix +api docs",
+            "Email @bob about status:doing",
+            "x marks the spot",
+            "(A) team offsite",
+            "2024-06-01 release notes",
+            "Link tix:01J0ZQ4S9M8Y6VXKHRTB3C2DNA and pri:A",
+            r"Keep \@literal and C:\path",
+        ];
+        for title in titles {
+            let line = format!("x (B) {} +web @home", escape(title));
+            let record = parse(&line, 1).unwrap();
+            assert_eq!(record.title, title, "{line}");
+            assert_eq!(record.project.as_deref(), Some("web"));
+            assert_eq!(record.priority, Priority::b);
+            assert_eq!(record.extras.labels.len(), 1);
+        }
+        assert_eq!(escape("3 x 4"), "3 x 4");
+    }
+
+    #[test]
+    fn identical_lines_get_their_own_key() {
+        let path = std::env::temp_dir().join(format!("tix_todotxt_test_{}.txt", std::process::id()));
+        std::fs::write(&path, "Call Bob\nCall Bob +web\nCall Bob\n").unwrap();
+        let records = read(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let records = records.unwrap();
+        let keys: Vec<&str> = records.iter().map(|record| record.extras.attributes[KEY].as_str()).collect();
+        assert_eq!(keys, ["Call Bob", "Call Bob", "Call Bob #2"]);
+    }
+
+    #[test]
+    fn no_task_text() {
+        assert!(parse("x (A) +web @home", 1).is_none());
+    }
+}
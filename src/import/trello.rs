@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::attrs::Extras;
+use crate::ffi::{Priority, Status};
+use crate::import::{self, ImportError, Record, Unmapped};
+use crate::prompt;
+
+/// Attribute that finds a card's ticket again on re-import
+pub const KEY: &str = "trello_card";
+
+/// Label added to tickets imported from archived cards
+pub const ARCHIVED_LABEL: &str = "archived";
+
+/// What to do with archived cards and the cards of archived lists
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Archived {
+    /// Leave them out of the import
+    Skip,
+    /// Import them as done, labelled "archived"
+    Done,
+}
+
+/// A board as saved by Trello's "Print, export and share" > "Export as JSON"
+#[derive(Deserialize)]
+struct Board {
+    lists: Vec<List>,
+    cards: Vec<Card>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+}
+
+#[derive(Deserialize)]
+struct List {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    id_short: Option<u64>,
+    #[serde(default)]
+    short_url: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checklist {
+    id_card: String,
+    name: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<CheckItem>,
+}
+
+#[derive(Deserialize)]
+struct CheckItem {
+    name: String,
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+/// List names and labels mapped to tix, read from a TOML file:
+///
+/// ```toml
+/// [lists]     # list name -> status
+/// "Up next" = "todo"
+/// [priority]  # label -> priority
+/// Urgent = "a"
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMapping {
+    #[serde(default)]
+    lists: HashMap<String, String>,
+    #[serde(default)]
+    priority: HashMap<String, String>,
+}
+
+/// List names common on Trello boards
+const LISTS: &[(&str, Status)] = &[
+    ("backlog", Status::backlog),
+    ("ideas", Status::backlog),
+    ("someday", Status::backlog),
+    ("to do", Status::todo),
+    ("todo", Status::todo),
+    ("next", Status::todo),
+    ("doing", Status::doing),
+    ("in progress", Status::doing),
+    ("review", Status::doing),
+    ("done", Status::done),
+    ("complete", Status::done),
+];
+
+/// Read the cards of a Trello board export. Lists missing from the mapping
+/// are asked about when `interactive`, and the cards of a list left
+/// unanswered are skipped. Otherwise they are counted in the returned
+/// report and their cards fall back to backlog.
+pub fn read(
+    path: &Path,
+    mapping: Option<&Path>,
+    archived: Archived,
+    interactive: bool,
+) -> Result<(Vec<Record>, Unmapped), ImportError> {
+    let raw: RawMapping = import::read_mapping(mapping)?;
+    let mut statuses: HashMap<String, Status> = LISTS.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    statuses.extend(import::parse_table(mapping, raw.lists)?);
+    let priorities: HashMap<String, Priority> = import::parse_table(mapping, raw.priority)?;
+
+    let text = std::fs::read_to_string(path).map_err(|e| ImportError::Read(path.to_path_buf(), e))?;
+    let board: Board =
+        serde_json::from_str(&text).map_err(|e| ImportError::Invalid(path.to_path_buf(), e.to_string()))?;
+    let lists: HashMap<&str, &List> = board.lists.iter().map(|list| (list.id.as_str(), list)).collect();
+
+    let mut checklists: HashMap<&str, Vec<&Checklist>> = HashMap::new();
+    for checklist in &board.checklists {
+        checklists.entry(checklist.id_card.as_str()).or_default().push(checklist);
+    }
+
+    // Ask once per list, in board order, before reading any card
+    let mut unmapped = Unmapped::new();
+    let mut list_status: HashMap<&str, Option<Status>> = HashMap::new();
+    let mut skipped: HashSet<&str> = HashSet::new();
+    for list in &board.lists {
+        let mut status = statuses.get(&list.name.trim().to_lowercase()).copied();
+        let has_open_cards = !list.closed && board.cards.iter().any(|card| card.id_list == list.id && !card.closed);
+        if status.is_none() && interactive && has_open_cards {
+            status = ask_status(&list.name)?;
+            if status.is_none() {
+                skipped.insert(list.id.as_str());
+            }
+        }
+        list_status.insert(list.id.as_str(), status);
+    }
+
+    let mut records = Vec::new();
+    for card in &board.cards {
+        let Some(list) = lists.get(card.id_list.as_str()) else {
+            return Err(ImportError::Invalid(
+                path.to_path_buf(),
+                format!("card '{}' is on an unknown list", card.name),
+            ));
+        };
+        let is_archived = card.closed || list.closed;
+        if skipped.contains(list.id.as_str()) || (is_archived && archived == Archived::Skip) {
+            continue;
+        }
+
+        let mut labels: Vec<String> = card
+            .labels
+            .iter()
+            .filter_map(|label| match label.name.trim() {
+                "" => label.color.clone(),
+                name => Some(name.to_string()),
+            })
+            .collect();
+        let priority = labels
+            .iter()
+            .find_map(|label| priorities.get(&label.to_lowercase()).copied())
+            .unwrap_or(Priority::z);
+
+        let status = if is_archived {
+            labels.push(ARCHIVED_LABEL.to_string());
+            Status::done
+        } else {
+            list_status[list.id.as_str()].unwrap_or_else(|| {
+                *unmapped.entry(("list", list.name.clone())).or_insert(0) += 1;
+                Status::backlog
+            })
+        };
+
+        let mut extras = Extras {
+            labels: labels.into_iter().collect(),
+            ..Extras::default()
+        };
+        extras.attributes.insert(KEY.to_string(), card.id.clone());
+        extras.attributes.insert("trello_list".to_string(), list.name.clone());
+        if let Some(url) = &card.short_url {
+            extras.attributes.insert("trello_url".to_string(), url.clone());
+        }
+
+        let body = body(&card.desc, checklists.get(card.id.as_str()).map_or(&[], Vec::as_slice));
+        records.push(Record {
+            name: card.id_short.map_or_else(|| card.id.clone(), |n| format!("#{n}")),
+            project: None,
+            id: None,
+            title: card.name.trim().to_string(),
+            body: (!body.is_empty()).then_some(body),
+            priority,
+            status,
+            extras,
+        });
+    }
+    Ok((records, unmapped))
+}
+
+/// Ask which status a list stands for; an empty answer skips its cards
+fn ask_status(list: &str) -> Result<Option<Status>, ImportError> {
+    loop {
+        let question = format!("Status for Trello list \"{list}\" (backlog/todo/doing/done, empty to skip its cards):");
+        let answer = prompt::ask(&question).map_err(ImportError::Prompt)?;
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        match Status::from_str(&answer, true) {
+            Ok(status) => return Ok(Some(status)),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
+
+/// The card description followed by its checklists as task lists
+fn body(desc: &str, checklists: &[&Checklist]) -> String {
+    let mut checklists = checklists.to_vec();
+    checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+    let mut sections = vec![desc.trim().to_string()];
+    for checklist in checklists {
+        let mut items: Vec<&CheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+
+        let mut section = format!("### {}\n", checklist.name.trim());
+        for item in items {
+            let mark = if item.state == "complete" { 'x' } else { ' ' };
+            section.push_str(&format!("\n- [{mark}] {}", item.name.trim()));
+        }
+        sections.push(section);
+    }
+    sections.retain(|section| !section.is_empty());
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = r#"{
+        "lists": [
+            {"id": "l1", "name": "To Do"},
+            {"id": "l2", "name": "Up next"},
+            {"id": "l3", "name": "Old", "closed": true}
+        ],
+        "cards": [
+            {"id": "c1", "name": " Fix login ", "desc": "Broken on mobile", "idList": "l1", "idShort": 7,
+             "shortUrl": "https://trello.com/c/abc", "labels": [{"name": "Urgent"}, {"name": "", "color": "red"}]},
+            {"id": "c2", "name": "Plan", "idList": "l2"},
+            {"id": "c3", "name": "Retired", "idList": "l1", "closed": true},
+            {"id": "c4", "name": "Ancient", "idList": "l3"}
+        ],
+        "checklists": [
+            {"idCard": "c1", "name": "Later", "pos": 2,
+             "checkItems": [{"name": "Docs", "state": "incomplete"}]},
+            {"idCard": "c1", "name": "Steps", "pos": 1,
+             "checkItems": [{"name": "Reproduce", "state": "complete", "pos": 2},
+                            {"name": "Find cause", "state": "incomplete", "pos": 1}]}
+        ]
+    }"#;
+
+    fn read_str(mapping: Option<&str>, archived: Archived) -> Result<(Vec<Record>, Unmapped), ImportError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("board.json");
+        std::fs::write(&path, BOARD).unwrap();
+        let mapping_path = dir.path().join("trello.toml");
+        if let Some(mapping) = mapping {
+            std::fs::write(&mapping_path, mapping).unwrap();
+        }
+        read(&path, mapping.map(|_| mapping_path.as_path()), archived, false)
+    }
+
+    #[test]
+    fn cards_and_checklists() {
+        let (records, _) = read_str(None, Archived::Skip).unwrap();
+        let login = &records[0];
+        assert_eq!(login.name, "#7");
+        assert_eq!(login.title, "Fix login");
+        assert_eq!(login.status, Status::todo);
+        assert_eq!(login.priority, Priority::z);
+        let labels: Vec<&str> = login.extras.labels.iter().map(String::as_str).collect();
+        assert_eq!(labels, ["Urgent", "red"]);
+        assert_eq!(login.extras.attributes[KEY], "c1");
+        assert_eq!(login.extras.attributes["trello_list"], "To Do");
+        assert_eq!(login.extras.attributes["trello_url"], "https://trello.com/c/abc");
+        assert_eq!(
+            login.body.as_deref(),
+            Some("Broken on mobile\n\n### Steps\n\n- [ ] Find cause\n- [x] Reproduce\n\n### Later\n\n- [ ] Docs")
+        );
+    }
+
+    #[test]
+    fn unmapped_lists_fall_back_to_backlog() {
+        let (records, unmapped) = read_str(None, Archived::Skip).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].status, Status::backlog);
+        assert_eq!(records[1].body, None);
+        assert_eq!(unmapped.get(&("list", "Up next".to_string())), Some(&1));
+
+        let mapping = "[lists]\n\"up next\" = \"doing\"\n[priority]\nurgent = \"a\"";
+        let (records, unmapped) = read_str(Some(mapping), Archived::Skip).unwrap();
+        assert!(unmapped.is_empty());
+        assert_eq!(records[0].priority, Priority::a);
+        assert_eq!(records[1].status, Status::doing);
+    }
+
+    #[test]
+    fn archived_cards() {
+        let (records, _) = read_str(None, Archived::Done).unwrap();
+        let archived: Vec<&Record> = records.iter().filter(|r| r.extras.labels.contains(ARCHIVED_LABEL)).collect();
+        let names: Vec<&str> = archived.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(names, ["Retired", "Ancient"]);
+        assert!(archived.iter().all(|r| r.status == Status::done));
+    }
+}
@@ -0,0 +1,334 @@
+/// Convert Jira wiki markup to Markdown: headings, lists, tables, quotes,
+/// code blocks, links, images and inline emphasis. Anything else is kept.
+pub fn to_markdown(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    // Closing tag of the {code} or {noformat} block we are in
+    let mut block: Option<&str> = None;
+    let mut quote = false;
+    let mut table_header = false;
+
+    for line in text.lines() {
+        if let Some(tag) = block {
+            match line.find(tag) {
+                Some(end) => {
+                    if !line[..end].trim().is_empty() {
+                        out.push(line[..end].to_string());
+                    }
+                    out.push("```".to_string());
+                    block = None;
+                }
+                None => out.push(line.to_string()),
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if let Some((tag, language, rest)) = code_block(trimmed) {
+            out.push(format!("```{language}"));
+            match rest.find(tag) {
+                Some(end) => {
+                    if !rest[..end].trim().is_empty() {
+                        out.push(rest[..end].to_string());
+                    }
+                    out.push("```".to_string());
+                }
+                None => {
+                    if !rest.trim().is_empty() {
+                        out.push(rest.to_string());
+                    }
+                    block = Some(tag);
+                }
+            }
+            continue;
+        }
+        if trimmed == "{quote}" {
+            quote = !quote;
+            continue;
+        }
+
+        let converted = if let Some(cells) = trimmed.strip_prefix("||") {
+            table_header = true;
+            let cells: Vec<String> = cells
+                .trim_end_matches("||")
+                .split("||")
+                .map(|cell| inline(cell.trim()))
+                .collect();
+            format!(
+                "| {} |\n|{}|",
+                cells.join(" | "),
+                vec![" --- "; cells.len()].join("|")
+            )
+        } else if trimmed.starts_with('|') && table_header {
+            let cells: Vec<String> = trimmed
+                .trim_matches('|')
+                .split('|')
+                .map(|cell| inline(cell.trim()))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        } else {
+            table_header = false;
+            block_line(trimmed)
+        };
+
+        if quote {
+            out.push(format!("> {converted}").trim_end().to_string());
+        } else {
+            out.push(converted);
+        }
+    }
+    if block.is_some() {
+        out.push("```".to_string());
+    }
+
+    // Only blank lines go, a nested first list item keeps its indent
+    out.join("\n").trim_matches('\n').to_string()
+}
+
+/// `{code:java}rest` -> ("{code}", "java", "rest"), and the same for {noformat}
+fn code_block(line: &str) -> Option<(&'static str, &str, &str)> {
+    let (tag, after) = if let Some(after) = line.strip_prefix("{code") {
+        ("{code}", after)
+    } else {
+        ("{noformat}", line.strip_prefix("{noformat")?)
+    };
+    let close = after.find('}')?;
+    let params = after[..close].trim_start_matches(':');
+    // {code:java} names the language, {code:title=x|language=java} may too
+    let language = params
+        .split('|')
+        .find_map(|param| match param.split_once('=') {
+            Some(("language" | "lang", value)) => Some(value),
+            Some(_) => None,
+            None => Some(param),
+        })
+        .unwrap_or("");
+    Some((tag, language, &after[close + 1..]))
+}
+
+/// Headings, block quotes, rules and lists
+fn block_line(line: &str) -> String {
+    if line.len() > 3
+        && line.starts_with('h')
+        && line.as_bytes()[2] == b'.'
+        && let Ok(level @ 1..=6) = line[1..2].parse::<usize>()
+    {
+        return format!("{} {}", "#".repeat(level), inline(line[3..].trim()));
+    }
+    if let Some(rest) = line.strip_prefix("bq.") {
+        return format!("> {}", inline(rest.trim()));
+    }
+    if line == "----" {
+        return "---".to_string();
+    }
+
+    let markers = line.bytes().take_while(|b| matches!(b, b'*' | b'#' | b'-')).count();
+    if markers > 0 && line[markers..].starts_with(' ') && (markers == 1 || !line.starts_with('-')) {
+        let prefix = &line[..markers];
+        let indent: String = prefix[..markers - 1]
+            .chars()
+            .map(|c| if c == '#' { "   " } else { "  " })
+            .collect();
+        let bullet = if prefix.ends_with('#') { "1." } else { "-" };
+        return format!("{indent}{bullet} {}", inline(line[markers..].trim()));
+    }
+
+    inline(line)
+}
+
+/// Inline markup: {{monospace}}, links, images, *bold*, _italic_, -strike-
+fn inline(text: &str) -> String {
+    // Pieces that are already Markdown and must not be touched again
+    let mut pieces: Vec<(String, bool)> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        pieces.push((rest[..start].to_string(), false));
+        pieces.push((format!("`{}`", &rest[start + 2..start + 2 + len]), true));
+        rest = &rest[start + 4 + len..];
+    }
+    pieces.push((rest.to_string(), false));
+
+    pieces
+        .into_iter()
+        .map(|(piece, done)| if done { piece } else { links(&piece) })
+        .collect()
+}
+
+/// Links and images, with emphasis applied to the text around them
+fn links(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    loop {
+        let link = rest.find('[').and_then(|start| {
+            let len = rest[start..].find(']')?;
+            Some((start, start + len + 1, link(&rest[start + 1..start + len])))
+        });
+        let image = rest.find('!').and_then(|start| {
+            let len = rest[start + 1..].find('!')?;
+            let inner = &rest[start + 1..start + 1 + len];
+            let source = inner.split('|').next().unwrap_or(inner);
+            let is_image = !source.is_empty() && !source.contains(char::is_whitespace) && source.contains('.');
+            is_image.then(|| (start, start + len + 2, format!("![]({source})")))
+        });
+
+        let next = match (link, image) {
+            (Some(l), Some(i)) => Some(if i.0 < l.0 { i } else { l }),
+            (l, i) => l.or(i),
+        };
+        let Some((start, end, markdown)) = next else {
+            out.push_str(&emphasis(rest));
+            return out;
+        };
+        out.push_str(&emphasis(&rest[..start]));
+        out.push_str(&markdown);
+        rest = &rest[end..];
+    }
+}
+
+/// `[text|url]`, `[url]` or `[~user]`
+fn link(inner: &str) -> String {
+    match inner.split_once('|') {
+        Some((text, url)) => format!("[{}]({})", emphasis(text), url.trim()),
+        None if inner.starts_with('~') => format!("@{}", &inner[1..]),
+        None if inner.contains("://") || inner.starts_with("mailto:") => format!("<{}>", inner.trim()),
+        None => format!("[{inner}]"),
+    }
+}
+
+fn emphasis(text: &str) -> String {
+    let text = strip_color(text);
+    let text = wrap(&text, '*', "**");
+    let text = wrap(&text, '_', "*");
+    wrap(&text, '-', "~~")
+}
+
+/// Drop {color:...} tags, keeping the coloured text
+fn strip_color(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{color") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replace `marker`-delimited spans with `markdown`-delimited ones. A span
+/// opens after a non-word character and closes before one, and neither end
+/// touches whitespace, so `a*b` and `well-known` are left alone.
+fn wrap(text: &str, marker: char, markdown: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let word = |i: usize| chars.get(i).is_some_and(|c| c.is_alphanumeric());
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let opens = chars[i] == marker
+            && (i == 0 || !word(i - 1))
+            && chars.get(i + 1).is_some_and(|c| !c.is_whitespace() && *c != marker);
+        let close = opens
+            .then(|| {
+                (i + 2..chars.len()).find(|&j| {
+                    chars[j] == marker && !chars[j - 1].is_whitespace() && !word(j + 1)
+                })
+            })
+            .flatten();
+
+        match close {
+            Some(j) => {
+                out.push_str(markdown);
+                out.extend(&chars[i + 1..j]);
+                out.push_str(markdown);
+                i = j + 1;
+            }
+            None => {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_markdown;
+
+    #[test]
+    fn headings() {
+        assert_eq!(to_markdown("h1. Title"), "# Title");
+        assert_eq!(to_markdown("h3. Some *bold* part"), "### Some **bold** part");
+        assert_eq!(to_markdown("h7. Not a heading"), "h7. Not a heading");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(to_markdown("* one\n** nested\n* two"), "- one\n  - nested\n- two");
+        assert_eq!(to_markdown("# first\n## sub\n# second"), "1. first\n   1. sub\n1. second");
+        assert_eq!(to_markdown("#* bullet in numbered"), "   - bullet in numbered");
+        assert_eq!(to_markdown("- dash"), "- dash");
+        assert_eq!(to_markdown("*bold* not a list"), "**bold** not a list");
+    }
+
+    #[test]
+    fn code_blocks() {
+        assert_eq!(to_markdown("{code:java}\nint x = 1;\n{code}"), "```java\nint x = 1;\n```");
+        assert_eq!(
+            to_markdown("{code:title=Foo.java|language=java}\nclass Foo {}\n{code}"),
+            "```java\nclass Foo {}\n```"
+        );
+        assert_eq!(to_markdown("{noformat}\n*kept* as is\n{noformat}"), "```\n*kept* as is\n```");
+        assert_eq!(to_markdown("{code}a *b*{code}"), "```\na *b*\n```");
+        assert_eq!(to_markdown("{code}\nnever closed"), "```\nnever closed\n```");
+    }
+
+    #[test]
+    fn quotes_and_rules() {
+        assert_eq!(to_markdown("bq. quoted"), "> quoted");
+        assert_eq!(to_markdown("{quote}\nhello *world*\n\nagain\n{quote}"), "> hello **world**\n>\n> again");
+        assert_eq!(to_markdown("above\n----\nbelow"), "above\n---\nbelow");
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            to_markdown("||Name||Count||\n|a|1|\n|*b*|2|"),
+            "| Name | Count |\n| --- | --- |\n| a | 1 |\n| **b** | 2 |"
+        );
+    }
+
+    #[test]
+    fn links_and_images() {
+        assert_eq!(to_markdown("see [Docs|https://example.com]"), "see [Docs](https://example.com)");
+        assert_eq!(to_markdown("[https://example.com]"), "<https://example.com>");
+        assert_eq!(to_markdown("[mailto:a@b.c]"), "<mailto:a@b.c>");
+        assert_eq!(to_markdown("ping [~alice]"), "ping @alice");
+        assert_eq!(to_markdown("[ref]"), "[ref]");
+        assert_eq!(to_markdown("!screen.png!"), "![](screen.png)");
+        assert_eq!(to_markdown("!screen.png|thumbnail!"), "![](screen.png)");
+        assert_eq!(to_markdown("Wow! Great!"), "Wow! Great!");
+    }
+
+    #[test]
+    fn emphasis() {
+        assert_eq!(to_markdown("*bold* _italic_ -strike-"), "**bold** *italic* ~~strike~~");
+        assert_eq!(to_markdown("well-known a*b 2*3"), "well-known a*b 2*3");
+        assert_eq!(to_markdown("use {{a*b*}} here"), "use `a*b*` here");
+        assert_eq!(to_markdown("{color:red}alert{color} now"), "alert now");
+    }
+
+    #[test]
+    fn nesting() {
+        assert_eq!(to_markdown("*bold _italic_*"), "**bold *italic***");
+        assert_eq!(to_markdown("[*Docs*|http://a.b]"), "[**Docs**](http://a.b)");
+        assert_eq!(to_markdown("* see [Docs|http://a.b] and {{x}}"), "- see [Docs](http://a.b) and `x`");
+        assert_eq!(to_markdown("{quote}\n* item\nh2. Head\n{quote}"), "> - item\n> ## Head");
+        assert_eq!(to_markdown("{color:red}*urgent*{color}"), "**urgent**");
+    }
+}
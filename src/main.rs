@@ -1,15 +1,76 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::io::IsTerminal;
+use std::path::PathBuf;
 use std::time::Instant;
 
-use crate::ffi::{Status, priority::Priority};
-
+use crate::color::{ColorChoice, Role, paint};
+use crate::exit::{ErrorFormat, ErrorKind};
+use crate::ffi::{Status, Ticket, priority::Priority};
+use crate::verbosity::{info, note};
+
+mod alias;
+mod attrs;
+mod backup;
+mod batch;
+mod branch;
+mod chart;
+mod color;
 mod editor;
+mod exit;
+mod export;
 mod ffi;
+mod git;
+mod history;
+mod import;
+mod metrics;
+mod projects;
+mod pager;
+mod prompt;
+mod serve;
+mod settings;
+mod stats;
+mod transfer;
+mod verbosity;
+mod workspace;
 
 #[derive(Parser)]
 #[command(name = "tix")]
 #[command(about = "Tix filesystem-based ticket management system", long_about = None)]
 struct Cli {
+    /// Run as if tix was started in this directory
+    #[arg(
+        short = 'C',
+        long = "workspace",
+        global = true,
+        env = "TIX_DIR",
+        value_name = "PATH"
+    )]
+    workspace: Option<PathBuf>,
+
+    /// When to colour output
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    color: Option<ColorChoice>,
+
+    /// Never send output through a pager
+    #[arg(long, global = true)]
+    no_pager: bool,
+
+    /// Only print errors on stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Explain what tix is doing; twice to trace library calls (also TIX_LOG)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Print how long each library and git call took
+    #[arg(long, global = true)]
+    timings: bool,
+
+    /// How to print errors on stderr
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value = "text")]
+    error_format: ErrorFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -22,6 +83,9 @@ enum Commands {
     /// Get or set configuration values
     Config(ConfigArgs),
 
+    /// List command aliases defined in config
+    Alias(AliasArgs),
+
     /// Switch to a different project
     Switch(SwitchArgs),
 
@@ -31,6 +95,9 @@ enum Commands {
     /// Update ticket status
     Mv(MvArgs),
 
+    /// Move a ticket to another project
+    Transfer(TransferArgs),
+
     /// List tickets
     #[command(name = "ls")]
     List(ListArgs),
@@ -38,6 +105,9 @@ enum Commands {
     /// Show detailed information about a ticket
     Show(ShowArgs),
 
+    /// Search ticket titles and bodies
+    Search(SearchArgs),
+
     /// Amend a ticket (modify title, body, priority)
     Amend(AmendArgs),
 
@@ -51,7 +121,10 @@ enum Commands {
     Log(LogArgs),
 
     /// List all local projects
-    Projects,
+    Projects(ProjectsArgs),
+
+    /// Rename, delete, archive or describe a project
+    Project(ProjectArgs),
 
     /// Remote repository operations
     Remote(RemoteArgs),
@@ -64,33 +137,101 @@ enum Commands {
 
     /// Clone a remote repository
     Clone(CloneArgs),
+
+    /// Show lead time and cycle time statistics
+    Metrics(MetricsArgs),
+
+    /// Draw a burndown or cumulative flow chart
+    Chart(ChartArgs),
+
+    /// Show a summary of tickets and activity
+    Stats(StatsArgs),
+
+    /// Import tickets from another tracker
+    Import(ImportArgs),
+
+    /// Export tickets for another tool
+    Export(ExportArgs),
+
+    /// Write every project and its full history to an archive
+    Backup(BackupArgs),
+
+    /// Recreate a workspace from a backup archive
+    Restore(RestoreArgs),
+
+    /// Serve tickets, projects and history as a JSON API
+    Serve(ServeArgs),
 }
 
 #[derive(Args)]
 struct AmendArgs {
     /// Ticket ID
-    ticket_id: String,
+    #[arg(required_unless_present = "filter", conflicts_with = "filter")]
+    ticket_id: Option<String>,
 
     /// New ticket title
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "filter")]
     title: Option<String>,
 
     /// New ticket body
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "filter")]
     body: Option<String>,
 
     /// New ticket priority
     #[arg(short, long)]
     priority: Option<Priority>,
+
+    /// Amend every ticket matching the conditions (e.g. "status=todo title~'login page'")
+    #[arg(short = 'w', long = "where", value_name = "FILTER")]
+    filter: Vec<String>,
+
+    /// New status for tickets matched by --where
+    #[arg(short, long, requires = "filter")]
+    status: Option<Status>,
+
+    /// Add a label to tickets matched by --where (can be specified multiple times)
+    #[arg(long, value_name = "LABEL", requires = "filter")]
+    label: Vec<String>,
+
+    /// Remove a label from tickets matched by --where (can be specified multiple times)
+    #[arg(long, value_name = "LABEL", requires = "filter")]
+    unlabel: Vec<String>,
+
+    /// Print what --where would change without changing anything
+    #[arg(short = 'n', long, requires = "filter")]
+    dry_run: bool,
+
+    /// Don't ask for confirmation
+    #[arg(short, long, requires = "filter")]
+    yes: bool,
 }
 
 #[derive(Args)]
 struct ConfigArgs {
-    /// Configuration key (e.g., user.name)
-    key: String,
+    /// Configuration key (e.g., user.name, ls.status)
+    #[arg(required_unless_present = "list")]
+    key: Option<String>,
 
     /// Configuration value (if setting)
     value: Option<String>,
+
+    /// List CLI configuration values
+    #[arg(short, long, conflicts_with_all = ["value", "unset"])]
+    list: bool,
+
+    /// Use the global CLI config file instead of the workspace one
+    #[arg(short, long)]
+    global: bool,
+
+    /// Remove a CLI configuration key
+    #[arg(long, conflicts_with = "value")]
+    unset: bool,
+}
+
+#[derive(Args)]
+struct AliasArgs {
+    /// Alias name (lists all aliases if omitted)
+    name: Option<String>,
 }
 
 #[derive(Args)]
@@ -114,8 +255,8 @@ struct AddArgs {
     body: Option<String>,
 
     /// Ticket priority
-    #[arg(short, long, default_value = "none")]
-    priority: Priority,
+    #[arg(short, long)]
+    priority: Option<Priority>,
 
     /// Ticket status
     #[arg(short, long)]
@@ -124,11 +265,25 @@ struct AddArgs {
 
 #[derive(Args)]
 struct MvArgs {
-    /// Ticket ID
-    ticket_id: String,
+    /// Ticket IDs, or - to read them from stdin (first column of each line)
+    #[arg(required = true, num_args = 1..)]
+    ticket_ids: Vec<String>,
 
     /// New status
     status: Status,
+
+    /// Skip unknown or invalid IDs instead of stopping at the first one
+    #[arg(short, long)]
+    keep_going: bool,
+}
+
+#[derive(Args)]
+struct TransferArgs {
+    /// Ticket ID
+    ticket_id: String,
+
+    /// Target project
+    project: String,
 }
 
 #[derive(Args)]
@@ -144,12 +299,50 @@ struct ListArgs {
     /// Filter by priority (can be specified multiple times)
     #[arg(short = 'p', long = "priority", value_enum)]
     priority: Vec<Priority>,
+
+    /// List tickets of another project without switching
+    #[arg(long, conflicts_with = "all_projects")]
+    project: Option<String>,
+
+    /// List tickets of all projects
+    #[arg(short, long)]
+    all_projects: bool,
 }
 
 #[derive(Args)]
 struct ShowArgs {
     /// Ticket ID
     ticket_id: String,
+
+    /// Look the ticket up in another project without switching
+    #[arg(long, conflicts_with = "all_projects")]
+    project: Option<String>,
+
+    /// Look the ticket up in all projects
+    #[arg(short, long)]
+    all_projects: bool,
+}
+
+#[derive(Args)]
+struct SearchArgs {
+    /// Text to search for (case-insensitive)
+    query: String,
+
+    /// Show detailed information
+    #[arg(short, long)]
+    long: bool,
+
+    /// Filter by status (can be specified multiple times)
+    #[arg(short = 's', long = "status", value_enum)]
+    status: Vec<Status>,
+
+    /// Search another project without switching
+    #[arg(long, conflicts_with = "all_projects")]
+    project: Option<String>,
+
+    /// Search all projects
+    #[arg(short, long)]
+    all_projects: bool,
 }
 
 #[derive(Args)]
@@ -167,14 +360,76 @@ struct LogArgs {
     since: Option<String>,
 }
 
+#[derive(Args)]
+struct ProjectsArgs {
+    /// Include archived projects
+    #[arg(short, long)]
+    all: bool,
+}
+
+#[derive(Args)]
+struct ProjectArgs {
+    #[command(subcommand)]
+    command: ProjectCommands,
+}
+
+#[derive(Subcommand)]
+enum ProjectCommands {
+    /// Rename a project
+    Rename {
+        /// Current project name
+        project: String,
+
+        /// New project name
+        new_name: String,
+    },
+
+    /// Delete a project and all its tickets
+    Delete {
+        /// Project name
+        project: String,
+
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Hide a project from `tix projects`
+    Archive {
+        /// Project name
+        project: String,
+    },
+
+    /// Show an archived project in `tix projects` again
+    Unarchive {
+        /// Project name
+        project: String,
+    },
+
+    /// Show or set a project description
+    Describe {
+        /// Project name
+        project: String,
+
+        /// New description (empty to clear)
+        description: Option<String>,
+    },
+}
+
 #[derive(Args)]
 struct RemoteArgs {
     #[command(subcommand)]
     command: Option<RemoteCommands>,
 
     /// Show remote URLs (when listing)
-    #[arg(short, long)]
-    verbose: bool,
+    #[arg(short = 'v', long)]
+    urls: bool,
+
+    // Shares the id of the global -v/--verbose, which keeps that out of this
+    // subcommand so that -v can mean --urls here
+    /// Explain what tix is doing (-v lists URLs here)
+    #[arg(id = "verbose", long = "verbose", action = clap::ArgAction::Count)]
+    _verbose: u8,
 }
 
 #[derive(Subcommand)]
@@ -195,6 +450,38 @@ struct CloneArgs {
     url: String,
 }
 
+#[derive(Args)]
+struct BackupArgs {
+    /// Archive to write, e.g. backup.tar.zst
+    file: PathBuf,
+}
+
+#[derive(Args)]
+struct RestoreArgs {
+    /// Archive written by `tix backup`
+    file: PathBuf,
+
+    /// Only verify the archive, without restoring it
+    #[arg(long)]
+    check: bool,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7777")]
+    bind: String,
+
+    /// Require this token as `Authorization: Bearer <token>` on every request
+    #[arg(long, env = "TIX_SERVE_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Let browser pages from this origin call the API, e.g.
+    /// http://localhost:5173 or * for any (can be specified multiple times)
+    #[arg(long, value_name = "ORIGIN")]
+    allow_origin: Vec<String>,
+}
+
 #[derive(Args)]
 struct PushArgs {
     /// Force push (--force)
@@ -206,42 +493,300 @@ struct PushArgs {
     force_with_lease: bool,
 }
 
+#[derive(Args)]
+struct MetricsArgs {
+    /// Only include tickets completed within this window (e.g., 30d, 2w)
+    #[arg(short, long, value_parser = history::parse_window)]
+    since: Option<i64>,
+
+    /// Only include this project (defaults to all projects)
+    #[arg(short, long)]
+    project: Option<String>,
+
+    /// Write per-ticket timings as CSV to a file ("-" for stdout)
+    #[arg(long, value_name = "FILE")]
+    csv: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ChartArgs {
+    /// Chart type
+    #[arg(value_enum)]
+    kind: chart::ChartKind,
+
+    /// Time window to chart (e.g., 2w, 30d)
+    #[arg(short, long, default_value = "2w", value_parser = history::parse_window)]
+    since: i64,
+
+    /// Project to chart (defaults to the current project)
+    #[arg(short, long)]
+    project: Option<String>,
+
+    /// Write the chart as SVG to a file instead of the terminal
+    #[arg(long, value_name = "FILE")]
+    svg: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    /// Aggregate across all projects
+    #[arg(short, long)]
+    all_projects: bool,
+}
+
+#[derive(Args)]
+struct ImportArgs {
+    /// Print what the import would change without changing anything
+    #[arg(short = 'n', long, global = true)]
+    dry_run: bool,
+
+    #[command(subcommand)]
+    source: ImportSource,
+}
+
+#[derive(Subcommand)]
+enum ImportSource {
+    /// Import issues exported with `gh issue list --json` or the REST API
+    Github {
+        /// JSON file with an array of issues
+        file: PathBuf,
+
+        /// TOML file mapping issue states and labels to status and priority
+        #[arg(short, long, value_name = "FILE")]
+        mapping: Option<PathBuf>,
+    },
+
+    /// Import issues from a Jira CSV export
+    Jira {
+        /// CSV file exported from a Jira issue search
+        file: PathBuf,
+
+        /// TOML file naming the columns and mapping statuses and priorities
+        #[arg(short, long, value_name = "FILE")]
+        mapping: Option<PathBuf>,
+    },
+
+    /// Import a todo.txt file; +project picks the project, @context adds a label
+    Todotxt {
+        /// todo.txt file
+        file: PathBuf,
+    },
+
+    /// Import tasks printed by `task export`
+    Taskwarrior {
+        /// JSON file with an array of tasks
+        file: PathBuf,
+
+        /// Task UUID to ticket ID map [default: .tix/.git/taskwarrior.json]
+        #[arg(long, value_name = "FILE")]
+        map: Option<PathBuf>,
+    },
+
+    /// Import the cards of a Trello board exported as JSON
+    Trello {
+        /// JSON file exported from the board menu
+        file: PathBuf,
+
+        /// TOML file mapping list names to status and labels to priority
+        #[arg(short, long, value_name = "FILE")]
+        mapping: Option<PathBuf>,
+
+        /// What to do with archived cards
+        #[arg(long, value_enum, default_value = "skip")]
+        archived: import::trello::Archived,
+    },
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// Write to this file instead of stdout
+    #[arg(short, long, global = true, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Export every project, not just the current one
+    #[arg(short, long, global = true)]
+    all_projects: bool,
+
+    #[command(subcommand)]
+    format: ExportFormat,
+}
+
+#[derive(Subcommand)]
+enum ExportFormat {
+    /// One todo.txt line per ticket, readable by `tix import todotxt`
+    Todotxt,
+
+    /// A JSON array of tasks for `task import`
+    Taskwarrior {
+        /// Task UUID to ticket ID map [default: .tix/.git/taskwarrior.json]
+        #[arg(long, value_name = "FILE")]
+        map: Option<PathBuf>,
+    },
+
+    /// A Markdown report grouped by status, or one file per ticket
+    Markdown {
+        /// Filter by status (can be specified multiple times)
+        #[arg(short = 's', long = "status", value_enum)]
+        status: Vec<Status>,
+
+        /// Filter by priority (can be specified multiple times)
+        #[arg(short = 'p', long = "priority", value_enum)]
+        priority: Vec<Priority>,
+
+        /// Only include tickets changed within this window (e.g., 7d, 2w)
+        #[arg(long, value_parser = history::parse_window)]
+        since: Option<i64>,
+
+        /// Write one file per ticket with YAML front matter into this directory
+        #[arg(long, value_name = "DIR")]
+        split: Option<PathBuf>,
+    },
+
+    /// An iCalendar file with one VTODO per ticket
+    Ical,
+
+    /// A static site with a ticket list, board and history per project
+    Html {
+        /// Directory to write the site to, created if missing
+        dir: PathBuf,
+    },
+}
+
 
 fn main() {
-    let cli = Cli::parse();
+    let args: Vec<String> = std::env::args_os()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    // Needed for errors raised before clap has parsed the arguments
+    let error_format = alias::raw_option(&args, None, "--error-format")
+        .and_then(|value| ErrorFormat::from_str(value, true).ok())
+        .unwrap_or(ErrorFormat::Text);
+
+    let args = match expand_aliases(args) {
+        Ok(args) => args,
+        Err(err) => exit::fail(&err, error_format),
+    };
+    let mut cli = match Cli::try_parse_from(args) {
+        Ok(cli) => cli,
+        Err(err) if err.use_stderr() && error_format == ErrorFormat::Json => {
+            let rendered = err.render().to_string();
+            let message = rendered.lines().next().unwrap_or_default();
+            let message = message.strip_prefix("error: ").unwrap_or(message);
+            exit::report(ErrorKind::Usage, message, error_format)
+        }
+        Err(err) => err.exit(),
+    };
+    verbosity::init(cli.quiet, cli.verbose, cli.timings);
+
+    // init, clone and restore create a workspace in place instead of searching upwards
+    let search = !matches!(cli.command, Commands::Init | Commands::Clone(_) | Commands::Restore(_));
+    if let Err(err) = workspace::enter(cli.workspace.as_deref(), search) {
+        let dir = cli.workspace.unwrap_or_else(|| PathBuf::from("."));
+        let message = format!("Cannot enter workspace {}: {err}", dir.display());
+        exit::fail(&anyhow::Error::new(err).context(message), cli.error_format);
+    }
+    resolve_paths(&mut cli.command);
+    if let Err(err) = settings::init() {
+        exit::fail(&err.into(), cli.error_format);
+    }
+    color::init(cli.color);
+    pager::init(cli.no_pager);
+    info!("workspace: {}", workspace::describe());
 
     let start = Instant::now();
     let result = match cli.command {
         Commands::Init => handle_init(),
         Commands::Config(args) => handle_config(args),
+        Commands::Alias(args) => handle_alias(args),
         Commands::Switch(args) => handle_switch(args),
         Commands::Add(args) => handle_add(args),
         Commands::Mv(args) => handle_mv(args),
+        Commands::Transfer(args) => handle_transfer(args),
         Commands::List(args) => handle_list(args),
-        Commands::Show(args) => handle_show(&args.ticket_id),
+        Commands::Show(args) => handle_show(args),
+        Commands::Search(args) => handle_search(args),
         Commands::Amend(args) => handle_amend(args),
         Commands::Undo => handle_undo(),
         Commands::Redo => handle_redo(),
         Commands::Log(args) => handle_log(args),
-        Commands::Projects => handle_projects(),
+        Commands::Projects(args) => handle_projects(args),
+        Commands::Project(args) => handle_project(args),
         Commands::Remote(args) => handle_remote(args),
         Commands::Push(args) => handle_push(args),
         Commands::Pull => handle_pull(),
         Commands::Clone(args) => handle_clone(args),
+        Commands::Metrics(args) => handle_metrics(args),
+        Commands::Chart(args) => handle_chart(args),
+        Commands::Stats(args) => handle_stats(args),
+        Commands::Import(args) => handle_import(args),
+        Commands::Export(args) => handle_export(args),
+        Commands::Backup(args) => handle_backup(args),
+        Commands::Restore(args) => handle_restore(args),
+        Commands::Serve(args) => handle_serve(args),
     };
     let duration = start.elapsed();
+    pager::finish();
+    verbosity::report_timings(duration);
 
     match result {
-        Ok(_) => {
-            eprintln!("Command completed in {:.2?}", duration);
-        }
+        Ok(_) => info!("command completed in {duration:.2?}"),
         Err(err) => {
-            eprintln!("Command failed in {:.2?}: {err}", duration);
-            std::process::exit(1);
+            info!("command failed in {duration:.2?}");
+            exit::fail(&err, cli.error_format);
         }
     }
 }
 
+/// Expand config aliases. This runs before clap, so the workspace whose
+/// config applies is located from the raw arguments.
+fn expand_aliases(args: Vec<String>) -> anyhow::Result<Vec<String>> {
+    let dir = alias::workspace_arg(&args)
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("TIX_DIR").map(PathBuf::from));
+    let settings = settings::load(workspace::locate(dir.as_deref()).as_deref())?;
+    Ok(alias::expand(args, &Cli::command(), &settings)?)
+}
+
+/// Resolve every file argument against the directory tix was started in,
+/// before anything reads or writes it. "-" stays stdin/stdout.
+fn resolve_paths(command: &mut Commands) {
+    fn resolve(path: &mut PathBuf) {
+        if path.as_os_str() != "-" {
+            *path = workspace::resolve(path);
+        }
+    }
+
+    match command {
+        Commands::Metrics(args) => args.csv.iter_mut().for_each(resolve),
+        Commands::Chart(args) => args.svg.iter_mut().for_each(resolve),
+        Commands::Backup(args) => resolve(&mut args.file),
+        Commands::Restore(args) => resolve(&mut args.file),
+        Commands::Import(args) => match &mut args.source {
+            ImportSource::Github { file, mapping }
+            | ImportSource::Jira { file, mapping }
+            | ImportSource::Trello { file, mapping, .. } => {
+                resolve(file);
+                mapping.iter_mut().for_each(resolve);
+            }
+            ImportSource::Todotxt { file } => resolve(file),
+            ImportSource::Taskwarrior { file, map } => {
+                resolve(file);
+                map.iter_mut().for_each(resolve);
+            }
+        },
+        Commands::Export(args) => {
+            args.output.iter_mut().for_each(resolve);
+            match &mut args.format {
+                ExportFormat::Taskwarrior { map } => map.iter_mut().for_each(resolve),
+                ExportFormat::Markdown { split, .. } => split.iter_mut().for_each(resolve),
+                ExportFormat::Html { dir } => resolve(dir),
+                ExportFormat::Todotxt | ExportFormat::Ical => {}
+            }
+        }
+        _ => {}
+    }
+}
+
 fn handle_init() -> anyhow::Result<()> {
     let result = ffi::init()?;
     println!("{result}");
@@ -249,15 +794,73 @@ fn handle_init() -> anyhow::Result<()> {
 }
 
 fn handle_config(args: ConfigArgs) -> anyhow::Result<()> {
+    let scope = if args.global {
+        settings::Scope::Global
+    } else {
+        settings::Scope::Workspace
+    };
+
+    if args.list {
+        let scope = args.global.then_some(scope);
+        for (key, value) in settings::get().entries(scope) {
+            println!("{key}={value}");
+        }
+        return Ok(());
+    }
+
+    let key = args.key.expect("clap requires a key unless --list is given");
+
+    if settings::is_cli_key(&key) {
+        if args.unset {
+            settings::unset(scope, &key)?;
+        } else if let Some(value) = args.value {
+            settings::set(scope, &key, &value)?;
+        } else if let Some(value) = settings::get().string(&key) {
+            println!("{value}");
+        }
+        return Ok(());
+    }
+
+    if args.global || args.unset {
+        anyhow::bail!(
+            "--global and --unset only apply to CLI keys: {}",
+            settings::KEYS.join(", ")
+        );
+    }
+
     if let Some(value) = args.value {
-        ffi::config_set(&args.key, &value)?;
+        ffi::config_set(&key, &value)?;
     } else {
-        let value = ffi::config_get(&args.key)?;
+        let value = ffi::config_get(&key)?;
         println!("{value}");
     }
     Ok(())
 }
 
+fn handle_alias(args: AliasArgs) -> anyhow::Result<()> {
+    let settings = settings::get();
+
+    if let Some(name) = args.name {
+        match settings.alias(&name) {
+            Some(definition) => println!("{definition}"),
+            None => anyhow::bail!("No alias named '{name}'"),
+        }
+        return Ok(());
+    }
+
+    let aliases = settings.aliases();
+    if aliases.is_empty() {
+        println!("No aliases defined. Add one with: tix config alias.<name> \"<command>\"");
+        return Ok(());
+    }
+
+    let width = aliases.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, definition) in aliases {
+        println!("{name:<width$} = {definition}");
+    }
+    Ok(())
+}
+
 fn handle_switch(args: SwitchArgs) -> anyhow::Result<()> {
     let result = ffi::switch(&args.project, args.create)?;
     println!("{result}");
@@ -265,69 +868,192 @@ fn handle_switch(args: SwitchArgs) -> anyhow::Result<()> {
 }
 
 fn handle_add(args: AddArgs) -> anyhow::Result<()> {
+    let settings = settings::get();
     let (title, body, priority, status) = if let Some(title) = args.title {
-        // Use provided arguments
-        (title, args.body, args.priority, args.status)
+        // Use provided arguments, falling back to configured defaults
+        (
+            title,
+            args.body,
+            args.priority
+                .or_else(|| settings.add_priority())
+                .unwrap_or(Priority::None),
+            args.status.or_else(|| settings.add_status()),
+        )
     } else {
         // Open editor for interactive input
         editor::open_editor_for_ticket()?
     };
 
-    let result = ffi::add(&title, body.as_deref(), priority, status)?;
-    println!("{result}");
+    let ticket_id = ffi::add(&title, body.as_deref(), priority, status)?;
+    println!("Created ticket: {ticket_id}");
     Ok(())
 }
 
 fn handle_mv(args: MvArgs) -> anyhow::Result<()> {
-    ffi::mv(&args.ticket_id, args.status)?;
-    println!(
-        "Ticket {} status updated to {:?}",
-        args.ticket_id, args.status
-    );
+    if let [ticket_id] = args.ticket_ids.as_slice()
+        && ticket_id != "-"
+    {
+        ffi::mv(ticket_id, args.status)?;
+        println!("Ticket {} status updated to {:?}", ticket_id, args.status);
+        return Ok(());
+    }
+
+    let ids = if args.ticket_ids.iter().any(|id| id == "-") {
+        batch::read_ids(std::io::stdin().lock())?
+    } else {
+        args.ticket_ids
+    };
+    if ids.is_empty() {
+        println!("No tickets to move.");
+        return Ok(());
+    }
+
+    let outcomes = batch::mv(&ids, args.status, args.keep_going)?;
+    print_outcomes(&outcomes);
+    let moved = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, batch::Outcome::Changed(..)))
+        .count();
+    println!("Moved {moved} tickets to {:?}", args.status);
+
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, batch::Outcome::Failed(..)))
+        .count();
+    if failed > 0 {
+        return Err(batch::BatchError::Partial(failed, outcomes.len()).into());
+    }
+    Ok(())
+}
+
+/// One line per ticket of a batch; failures go to stderr
+fn print_outcomes(outcomes: &[batch::Outcome]) {
+    let width = outcomes
+        .iter()
+        .filter_map(|outcome| match outcome {
+            batch::Outcome::Changed(ticket, _) | batch::Outcome::Unchanged(ticket) => {
+                Some(ticket.title.len())
+            }
+            batch::Outcome::Failed(..) => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    for outcome in outcomes {
+        match outcome {
+            batch::Outcome::Changed(ticket, change) => println!(
+                "{} {:<width$} {change}",
+                paint(Role::Id, &ticket.id),
+                ticket.title
+            ),
+            batch::Outcome::Unchanged(ticket) => println!(
+                "{} {:<width$} unchanged",
+                paint(Role::Id, &ticket.id),
+                ticket.title
+            ),
+            batch::Outcome::Failed(id, err) => eprintln!("{id}: {err}"),
+        }
+    }
+}
+
+fn handle_transfer(args: TransferArgs) -> anyhow::Result<()> {
+    let result = transfer::transfer(&args.ticket_id, &args.project)?;
+    println!("{result}");
     Ok(())
 }
 
 fn handle_list(args: ListArgs) -> anyhow::Result<()> {
-    // Default to todo and doing if no status filter provided
+    // Default to ls.status (todo and doing unless configured) if no status filter provided
     let statuses = if args.status.is_empty() {
-        vec![Status::todo, Status::doing]
+        settings::get().list_statuses()
     } else {
         args.status
     };
 
-    let tickets = ffi::list(args.long, statuses, args.priority)?;
+    let tickets = match selected_projects(args.project, args.all_projects)? {
+        Some(projects) => {
+            let mut tickets = Vec::new();
+            for project in projects {
+                for ticket in branch::tickets(&project)? {
+                    let matches = statuses.contains(&ticket.status)
+                        && (args.priority.is_empty() || args.priority.contains(&ticket.priority));
+                    if matches {
+                        tickets.push((Some(project.clone()), ticket));
+                    }
+                }
+            }
+            tickets
+        }
+        None => ffi::list(args.long, statuses, args.priority)?
+            .into_iter()
+            .map(|ticket| (None, ticket))
+            .collect(),
+    };
 
     if tickets.is_empty() {
         println!("No tickets found.");
         return Ok(());
     }
 
-    if args.long {
-        // Find max title length for alignment
-        let max_title_len = tickets.iter().map(|t| t.title.len()).max().unwrap_or(0);
+    pager::start(tickets.len());
+    print_tickets(&tickets, args.long);
+    Ok(())
+}
+
+/// Print tickets in the `ls` format, with a project column after the ID when
+/// known, so the ID stays first for `tix mv -`
+fn print_tickets(tickets: &[(Option<String>, Ticket)], long: bool) {
+    let project_width = tickets
+        .iter()
+        .filter_map(|(project, _)| project.as_ref().map(|p| p.len()))
+        .max();
+
+    // Find max title length for alignment
+    let max_title_len = tickets.iter().map(|(_, t)| t.title.len()).max().unwrap_or(0);
+
+    for (project, ticket) in tickets {
+        print!("{} ", paint(Role::Id, &ticket.id));
+        if let (Some(project), Some(width)) = (project, project_width) {
+            print!("{} ", paint(Role::Project, &format!("{project:<width$}")));
+        }
 
-        for ticket in tickets {
+        if long {
             // Detailed view with aligned columns: ID title [priority] status
             println!(
-                "{} {:<width$} [{:?}] {:?}",
-                ticket.id,
+                "{:<width$} {} {}",
                 ticket.title,
-                ticket.priority,
-                ticket.status,
+                paint(
+                    Role::Priority(ticket.priority),
+                    &format!("[{:?}]", ticket.priority)
+                ),
+                paint(Role::Status(ticket.status), &format!("{:?}", ticket.status)),
                 width = max_title_len
             );
-        }
-    } else {
-        for ticket in tickets {
+        } else {
             // Simple view: just ID and title
-            println!("{} {}", ticket.id, ticket.title);
+            println!("{}", ticket.title);
         }
     }
+}
 
-    Ok(())
+/// Projects selected by --project/--all-projects, or None for the current one
+fn selected_projects(
+    project: Option<String>,
+    all_projects: bool,
+) -> anyhow::Result<Option<Vec<String>>> {
+    if all_projects {
+        return Ok(Some(ffi::projects()?));
+    }
+    Ok(project.map(|project| vec![project]))
 }
 
-fn handle_show(ticket_id: &str) -> anyhow::Result<()> {
+fn handle_show(args: ShowArgs) -> anyhow::Result<()> {
+    let ticket_id = args.ticket_id.as_str();
+
+    if let Some(projects) = selected_projects(args.project, args.all_projects)? {
+        return handle_show_in_projects(ticket_id, &projects);
+    }
+
     // Check if the ticket_id contains a field specifier (e.g., "01K3XXX:title")
     if let Some(colon_pos) = ticket_id.find(':') {
         let id = &ticket_id[..colon_pos];
@@ -350,44 +1076,171 @@ fn handle_show(ticket_id: &str) -> anyhow::Result<()> {
                 let priority = ffi::show_priority(id)?;
                 println!("{:?}", priority);
             }
-            _ => {
-                eprintln!(
-                    "Unknown field: {}. Valid fields are: title, body, status, priority",
-                    field
-                );
-                std::process::exit(1);
-            }
+            _ => anyhow::bail!(
+                "Unknown field: {}. Valid fields are: title, body, status, priority",
+                field
+            ),
         }
     } else {
         // No field specifier, show the full ticket
         let ticket = ffi::show(ticket_id)?;
-        println!("ID: {}", ticket.id);
-        println!("Title: {}", ticket.title);
-        println!("Status: {:?}", ticket.status);
-        println!("Priority: {:?}", ticket.priority);
-        if let Some(body) = ticket.body {
-            println!("Body:\n{body}");
+        pager::start(ticket_lines(&ticket));
+        print_ticket(&ticket);
+    }
+    Ok(())
+}
+
+/// Show a ticket read from other project branches, without switching
+fn handle_show_in_projects(ticket_id: &str, projects: &[String]) -> anyhow::Result<()> {
+    let (id, field) = match ticket_id.split_once(':') {
+        Some((id, field)) => (id, Some(field)),
+        None => (ticket_id, None),
+    };
+
+    for project in projects {
+        let Some(ticket) = branch::find(project, id)? else {
+            continue;
+        };
+
+        match field {
+            Some("title") => println!("{}", ticket.title),
+            Some("body") => println!("{}", ticket.body.unwrap_or_default()),
+            Some("status") => println!("{:?}", ticket.status),
+            Some("priority") => println!("{:?}", ticket.priority),
+            Some(field) => anyhow::bail!(
+                "Unknown field: {}. Valid fields are: title, body, status, priority",
+                field
+            ),
+            None => {
+                pager::start(ticket_lines(&ticket) + 1);
+                println!(
+                    "{} {}",
+                    paint(Role::Header, "Project:"),
+                    paint(Role::Project, project)
+                );
+                print_ticket(&ticket);
+            }
         }
+        return Ok(());
     }
+
+    Err(ffi::TixError::TicketNotFound.into())
+}
+
+/// Number of lines `print_ticket` writes
+fn ticket_lines(ticket: &Ticket) -> usize {
+    4 + ticket.body.as_ref().map_or(0, |body| body.lines().count() + 1)
+}
+
+fn print_ticket(ticket: &Ticket) {
+    let header = |name: &str| paint(Role::Header, &format!("{name}:"));
+    println!("{} {}", header("ID"), paint(Role::Id, &ticket.id));
+    println!("{} {}", header("Title"), ticket.title);
+    println!(
+        "{} {}",
+        header("Status"),
+        paint(Role::Status(ticket.status), &format!("{:?}", ticket.status))
+    );
+    println!(
+        "{} {}",
+        header("Priority"),
+        paint(Role::Priority(ticket.priority), &format!("{:?}", ticket.priority))
+    );
+    if let Some(body) = &ticket.body {
+        println!("{}\n{body}", header("Body"));
+    }
+}
+
+fn handle_search(args: SearchArgs) -> anyhow::Result<()> {
+    let show_project = args.project.is_some() || args.all_projects;
+    let projects = selected_projects(args.project, args.all_projects)?
+        .map_or_else(|| git::current_branch().map(|p| vec![p]), Ok)?;
+    let query = args.query.to_lowercase();
+
+    let mut tickets = Vec::new();
+    for project in projects {
+        for ticket in branch::tickets(&project)? {
+            if !args.status.is_empty() && !args.status.contains(&ticket.status) {
+                continue;
+            }
+            let matches = ticket.title.to_lowercase().contains(&query)
+                || ticket
+                    .body
+                    .as_deref()
+                    .is_some_and(|body| body.to_lowercase().contains(&query));
+            if matches {
+                tickets.push((show_project.then(|| project.clone()), ticket));
+            }
+        }
+    }
+
+    if tickets.is_empty() {
+        println!("No tickets found.");
+        return Ok(());
+    }
+
+    pager::start(tickets.len());
+    print_tickets(&tickets, args.long);
     Ok(())
 }
 
 fn handle_amend(args: AmendArgs) -> anyhow::Result<()> {
+    let Some(ticket_id) = args.ticket_id.as_deref() else {
+        return handle_bulk_amend(args);
+    };
+
     // Check if any flags are provided
     let has_flags = args.title.is_some() || args.body.is_some() || args.priority.is_some();
 
     if has_flags {
         // Use provided flags directly - if user provided it, pass it
-        ffi::amend(&args.ticket_id, args.title.as_deref(), args.body.as_deref(), args.priority)?;
+        ffi::amend(ticket_id, args.title.as_deref(), args.body.as_deref(), args.priority)?;
     } else {
         // No flags provided - open editor (returns only changed fields)
-        let current_ticket = ffi::show(&args.ticket_id)?;
+        let current_ticket = ffi::show(ticket_id)?;
         let (title_opt, body_opt, priority_opt) = editor::open_editor_for_ticket_amend(&current_ticket)?;
         
-        ffi::amend(&args.ticket_id, title_opt.as_deref(), body_opt.as_deref(), priority_opt)?;
+        ffi::amend(ticket_id, title_opt.as_deref(), body_opt.as_deref(), priority_opt)?;
     }
 
-    println!("Ticket {} amended successfully", args.ticket_id);
+    println!("Ticket {} amended successfully", ticket_id);
+    Ok(())
+}
+
+fn handle_bulk_amend(args: AmendArgs) -> anyhow::Result<()> {
+    if args.priority.is_none() && args.status.is_none() && args.label.is_empty() && args.unlabel.is_empty() {
+        anyhow::bail!("--where needs --priority, --status, --label or --unlabel");
+    }
+    let filter = batch::Filter::parse(&args.filter)?;
+    let change = batch::Change {
+        priority: args.priority,
+        status: args.status,
+        label: args.label,
+        unlabel: args.unlabel,
+    };
+
+    let outcomes = batch::plan(&filter, &change)?;
+    let count = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, batch::Outcome::Changed(..)))
+        .count();
+    if count == 0 {
+        println!("No tickets to amend.");
+        return Ok(());
+    }
+
+    print_outcomes(&outcomes);
+    if args.dry_run {
+        println!("Would amend {count} tickets");
+        return Ok(());
+    }
+    if !args.yes && !prompt::confirm(&format!("Amend {count} tickets?"))? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let amended = batch::amend(&outcomes, &change, &args.filter.join(" "))?;
+    println!("Amended {amended} tickets");
     Ok(())
 }
 
@@ -405,40 +1258,91 @@ fn handle_redo() -> anyhow::Result<()> {
 
 fn handle_log(args: LogArgs) -> anyhow::Result<()> {
     let result = ffi::log(args.oneline, args.limit, args.since.as_deref())?;
-    
-    if let Ok(mut pager) = std::process::Command::new("less")
-        .args(["-R", "-F"])
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-    {
-        if let Some(stdin) = pager.stdin.as_mut() {
-            use std::io::Write;
-            let _ = stdin.write_all(result.as_bytes());
-        }
-        let _ = pager.wait();
-    } else {
-        print!("{result}");
-    }
-    
+
+    pager::page(&result);
     Ok(())
 }
 
-fn handle_projects() -> anyhow::Result<()> {
+fn handle_projects(args: ProjectsArgs) -> anyhow::Result<()> {
     let projects = ffi::projects()?;
-    
+    let info = projects::info()?;
+
+    // The current project is always listed, even when archived
+    let projects: Vec<(usize, &String)> = projects
+        .iter()
+        .enumerate()
+        .filter(|(i, project)| {
+            args.all || *i == 0 || !info.get(*project).is_some_and(|p| p.archived)
+        })
+        .collect();
+
     if projects.is_empty() {
         println!("No projects found.");
         return Ok(());
     }
 
-    for (i, project) in projects.iter().enumerate() {
-        if i == 0 {
-            println!("* {}", project);
+    let width = projects.iter().map(|(_, p)| p.len()).max().unwrap_or(0);
+
+    for (i, project) in projects {
+        let (marker, role) = if i == 0 {
+            ("*", Role::Current)
         } else {
-            println!("  {}", project);
+            (" ", Role::Project)
+        };
+        let mut details = Vec::new();
+        if let Some(project_info) = info.get(project) {
+            // The first line of a longer description keeps one project per line
+            details.extend(project_info.description.as_deref().and_then(|d| d.lines().next()));
+            if project_info.archived {
+                details.push("(archived)");
+            }
+        }
+
+        if details.is_empty() {
+            println!("{} {}", paint(role, marker), paint(role, project));
+        } else {
+            println!(
+                "{} {}  {}",
+                paint(role, marker),
+                paint(role, &format!("{project:<width$}")),
+                details.join(" ")
+            );
         }
     }
-    
+
+    Ok(())
+}
+
+fn handle_project(args: ProjectArgs) -> anyhow::Result<()> {
+    let result = match args.command {
+        ProjectCommands::Rename { project, new_name } => projects::rename(&project, &new_name)?,
+        ProjectCommands::Delete { project, yes } => {
+            let question = format!("Delete project '{project}' and all its tickets?");
+            if !yes && !prompt::confirm(&question)? {
+                println!("Aborted.");
+                return Ok(());
+            }
+            projects::delete(&project)?
+        }
+        ProjectCommands::Archive { project } => projects::archive(&project, true)?,
+        ProjectCommands::Unarchive { project } => projects::archive(&project, false)?,
+        ProjectCommands::Describe {
+            project,
+            description: Some(description),
+        } => projects::describe(&project, &description)?,
+        ProjectCommands::Describe {
+            project,
+            description: None,
+        } => {
+            git::ensure_project(&project)?;
+            let info = projects::info()?;
+            info.get(&project)
+                .and_then(|p| p.description.clone())
+                .unwrap_or_default()
+        }
+    };
+
+    println!("{result}");
     Ok(())
 }
 
@@ -450,7 +1354,7 @@ fn handle_remote(args: RemoteArgs) -> anyhow::Result<()> {
         }
         None => {
             // List remotes
-            let result = ffi::remote(args.verbose)?;
+            let result = ffi::remote(args.urls)?;
             
             if result.is_empty() {
                 println!("No remotes configured.");
@@ -464,17 +1368,15 @@ fn handle_remote(args: RemoteArgs) -> anyhow::Result<()> {
 }
 
 fn handle_push(args: PushArgs) -> anyhow::Result<()> {
-    eprint!("Pushing...");
+    note!("Pushing...");
     let result = ffi::push(args.force, args.force_with_lease)?;
-    eprintln!();
     println!("{result}");
     Ok(())
 }
 
 fn handle_pull() -> anyhow::Result<()> {
-    eprint!("Pulling...");
+    note!("Pulling...");
     let result = ffi::pull()?;
-    eprintln!();
     println!("{result}");
     Ok(())
 }
@@ -484,3 +1386,288 @@ fn handle_clone(args: CloneArgs) -> anyhow::Result<()> {
     println!("{result}");
     Ok(())
 }
+
+fn handle_backup(args: BackupArgs) -> anyhow::Result<()> {
+    let manifest = backup::create(&args.file)?;
+    println!(
+        "Backed up {} projects ({} tickets) to {}",
+        manifest.projects.len(),
+        manifest.tickets(),
+        args.file.display()
+    );
+    Ok(())
+}
+
+fn handle_restore(args: RestoreArgs) -> anyhow::Result<()> {
+    if args.check {
+        let manifest = backup::check(&args.file)?;
+        println!(
+            "{} is a valid backup of {} projects ({} tickets), made {} by tix {}",
+            args.file.display(),
+            manifest.projects.len(),
+            manifest.tickets(),
+            manifest.created,
+            manifest.tix_version
+        );
+        return Ok(());
+    }
+
+    let (manifest, dir) = backup::restore(&args.file)?;
+    println!(
+        "Restored {} projects ({} tickets) into {}",
+        manifest.projects.len(),
+        manifest.tickets(),
+        dir.display()
+    );
+    Ok(())
+}
+
+fn handle_serve(args: ServeArgs) -> anyhow::Result<()> {
+    git::current_branch()?;
+    let server = serve::bind(&args.bind)?;
+    let loopback = server
+        .server_addr()
+        .to_ip()
+        .is_some_and(|addr| addr.ip().is_loopback());
+    if !loopback && args.token.is_none() {
+        eprintln!("Warning: anyone who can reach {} can change tickets, consider --token", args.bind);
+    }
+    note!("Listening on http://{}", args.bind);
+    serve::run(server, args.token, args.allow_origin);
+    Ok(())
+}
+
+fn handle_metrics(args: MetricsArgs) -> anyhow::Result<()> {
+    let projects = match args.project {
+        Some(project) => vec![project],
+        None => ffi::projects()?,
+    };
+
+    let records = metrics::collect(&projects, args.since)?;
+
+    if let Some(path) = args.csv {
+        if path.as_os_str() == "-" {
+            metrics::write_csv(&records, &mut std::io::stdout().lock())?;
+        } else {
+            let mut file = std::fs::File::create(&path)?;
+            metrics::write_csv(&records, &mut file)?;
+            note!("Wrote {} tickets to {}", records.len(), path.display());
+        }
+        return Ok(());
+    }
+
+    if records.is_empty() {
+        println!("No completed tickets found.");
+        return Ok(());
+    }
+
+    print!("{}", metrics::render(&records));
+    Ok(())
+}
+
+fn handle_chart(args: ChartArgs) -> anyhow::Result<()> {
+    let project = args.project.as_deref().unwrap_or("HEAD");
+    let tickets = history::tickets(project)?;
+    let Some(days) = chart::daily_counts(&tickets, args.since) else {
+        anyhow::bail!("The --since window is too large");
+    };
+
+    if let Some(path) = args.svg {
+        std::fs::write(&path, chart::render_svg(args.kind, &days))?;
+        println!("Wrote chart to {}", path.display());
+    } else {
+        print!(
+            "{}",
+            chart::render(args.kind, &days, chart::terminal_width())
+        );
+    }
+    Ok(())
+}
+
+fn handle_stats(args: StatsArgs) -> anyhow::Result<()> {
+    let projects = if args.all_projects {
+        ffi::projects()?
+    } else {
+        vec![git::current_branch()?]
+    };
+
+    let stats = stats::collect(projects)?;
+    print!("{}", stats::render(&stats));
+    Ok(())
+}
+
+fn handle_import(args: ImportArgs) -> anyhow::Result<()> {
+    let mut uuid_map = None;
+    let (records, unmapped, key, source) = match &args.source {
+        ImportSource::Github { file, mapping } => (
+            import::github::read(file, mapping.as_deref())?,
+            import::Unmapped::new(),
+            import::github::KEY,
+            "github",
+        ),
+        ImportSource::Jira { file, mapping } => {
+            let (records, unmapped) = import::jira::read(file, mapping.as_deref())?;
+            (records, unmapped, import::jira::KEY, "jira")
+        }
+        ImportSource::Todotxt { file } => (
+            import::todotxt::read(file)?,
+            import::Unmapped::new(),
+            import::todotxt::KEY,
+            "todo.txt",
+        ),
+        ImportSource::Taskwarrior { file, map } => {
+            let path = map.clone().unwrap_or_else(import::taskwarrior::UuidMap::default_path);
+            let map = uuid_map.insert(import::taskwarrior::UuidMap::read(path)?);
+            (
+                import::taskwarrior::read(file, map)?,
+                import::Unmapped::new(),
+                import::taskwarrior::KEY,
+                "taskwarrior",
+            )
+        }
+        ImportSource::Trello { file, mapping, archived } => {
+            // Ask about unknown lists only when someone can answer
+            let interactive = std::io::stdin().is_terminal();
+            let (records, unmapped) = import::trello::read(file, mapping.as_deref(), *archived, interactive)?;
+            (records, unmapped, import::trello::KEY, "trello")
+        }
+    };
+
+    // Report every value the mapping misses before anything is written
+    if !unmapped.is_empty() {
+        eprintln!("No mapping for:");
+        for ((field, value), count) in &unmapped {
+            let records = if *count == 1 { "record" } else { "records" };
+            eprintln!("  {field} \"{value}\" ({count} {records})");
+        }
+        if !args.dry_run {
+            return Err(import::ImportError::Unmapped(unmapped.len()).into());
+        }
+    }
+    if records.is_empty() {
+        println!("Nothing to import.");
+        return Ok(());
+    }
+
+    let batches = import::plan(records, key)?;
+    if args.dry_run {
+        for batch in &batches {
+            let ids: Vec<String> = batch
+                .steps
+                .iter()
+                .map(|step| match &step.action {
+                    import::Action::Create => "-".repeat(26),
+                    import::Action::Update(ticket, ..) | import::Action::Unchanged(ticket) => ticket.id.clone(),
+                })
+                .collect();
+            print_import(batch, &ids, batches.len() > 1, "Would import");
+        }
+        return Ok(());
+    }
+
+    let ids = import::apply(&batches, source)?;
+    if let Some(map) = &mut uuid_map {
+        map.update(&batches, &ids)?;
+    }
+    for (batch, ids) in batches.iter().zip(&ids) {
+        print_import(batch, ids, batches.len() > 1, "Imported");
+    }
+    Ok(())
+}
+
+/// One line per imported record, then totals
+fn print_import(batch: &import::Batch, ids: &[String], with_project: bool, verb: &str) {
+    let steps = &batch.steps;
+    if with_project {
+        let new = if batch.create { " (new)" } else { "" };
+        println!("{}{new}:", paint(Role::Project, &batch.project));
+    }
+    let width = steps.iter().map(|s| s.record.name.len()).max().unwrap_or(0);
+    let (mut created, mut updated, mut unchanged) = (0, 0, 0);
+
+    for (step, id) in steps.iter().zip(ids) {
+        let action = match &step.action {
+            import::Action::Create => {
+                created += 1;
+                "created".to_string()
+            }
+            import::Action::Update(_, _, changes) => {
+                updated += 1;
+                format!("updated {}", changes.join(", "))
+            }
+            import::Action::Unchanged(_) => {
+                unchanged += 1;
+                "unchanged".to_string()
+            }
+        };
+        println!(
+            "{:<width$} {} {} ({action})",
+            step.record.name,
+            paint(Role::Id, id),
+            step.record.title
+        );
+    }
+    println!(
+        "{verb} {} tickets: {created} created, {updated} updated, {unchanged} unchanged",
+        steps.len()
+    );
+}
+
+fn handle_export(args: ExportArgs) -> anyhow::Result<()> {
+    let items = export::collect(args.all_projects)?;
+    if let ExportFormat::Html { dir } = &args.format {
+        let pages = export::html::write(&items, dir)?;
+        note!("Wrote {pages} pages for {} tickets to {}", items.len(), dir.display());
+        return Ok(());
+    }
+    if let ExportFormat::Markdown { status, priority, since, split: Some(dir) } = &args.format {
+        let filter = export::markdown::Filter { statuses: status.clone(), priorities: priority.clone(), since: *since };
+        let selected = export::markdown::select(&items, &filter)?;
+        export::markdown::write_files(&selected, dir)?;
+        note!("Wrote {} tickets to {}", selected.len(), dir.display());
+        return Ok(());
+    }
+
+    let path = args.output.filter(|path| path.as_os_str() != "-");
+    let mut writer: Box<dyn std::io::Write> = match &path {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    let mut count = items.len();
+    match args.format {
+        ExportFormat::Todotxt => export::todotxt::write(&items, &mut writer)?,
+        ExportFormat::Taskwarrior { map } => {
+            let path = map.unwrap_or_else(import::taskwarrior::UuidMap::default_path);
+            let map = import::taskwarrior::UuidMap::read(path)?;
+            export::taskwarrior::write(&items, &map, &mut writer)?
+        }
+        ExportFormat::Markdown { status, priority, since, .. } => {
+            let filter = export::markdown::Filter { statuses: status, priorities: priority, since };
+            let selected = export::markdown::select(&items, &filter)?;
+            let title = if args.all_projects {
+                "All projects".to_string()
+            } else {
+                git::current_branch()?
+            };
+            count = selected.len();
+            export::markdown::write(&selected, &title, since, &mut writer)?
+        }
+        ExportFormat::Ical => {
+            let name = if args.all_projects {
+                "tix".to_string()
+            } else {
+                git::current_branch()?
+            };
+            let changed = export::last_changed(&items, i64::MIN)?;
+            export::ical::write(&items, &changed, &name, &mut writer)?
+        }
+        ExportFormat::Html { .. } => unreachable!("html is written to a directory above"),
+    }
+    writer.flush()?;
+
+    if let Some(path) = path {
+        note!("Wrote {count} tickets to {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::ffi::Priority;
+use crate::git::GitError;
+use crate::history::{self, TicketHistory};
+
+type Measure = fn(&TicketHistory) -> Option<i64>;
+
+/// A completed ticket and the project it was completed on
+pub struct Record {
+    pub project: String,
+    pub ticket: TicketHistory,
+}
+
+/// Percentile summary of a set of durations, in seconds
+pub struct Summary {
+    pub count: usize,
+    pub median: i64,
+    pub p85: i64,
+    pub p95: i64,
+}
+
+/// Collect tickets completed within the window on the given projects
+pub fn collect(projects: &[String], since: Option<i64>) -> Result<Vec<Record>, GitError> {
+    let cutoff = since.map(|window| history::now() - window);
+    let mut records = Vec::new();
+
+    for project in projects {
+        for ticket in history::tickets(project)? {
+            let Some(done_at) = ticket.done_at() else {
+                continue;
+            };
+            if cutoff.is_some_and(|cutoff| done_at < cutoff) {
+                continue;
+            }
+            records.push(Record {
+                project: project.clone(),
+                ticket,
+            });
+        }
+    }
+
+    Ok(records)
+}
+
+pub fn summarize(mut values: Vec<i64>) -> Option<Summary> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+
+    Some(Summary {
+        count: values.len(),
+        median: percentile(&values, 50.0),
+        p85: percentile(&values, 85.0),
+        p95: percentile(&values, 95.0),
+    })
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[i64], p: f64) -> i64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Render lead and cycle time tables grouped by project and priority
+pub fn render(records: &[Record]) -> String {
+    let mut out = String::new();
+
+    let sections: [(&str, Measure); 2] = [
+        ("Lead time (created -> done)", TicketHistory::lead_time),
+        ("Cycle time (first doing -> done)", TicketHistory::cycle_time),
+    ];
+
+    for (i, (heading, measure)) in sections.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "{heading}, in days");
+        let _ = writeln!(
+            out,
+            "{:<24} {:>5} {:>8} {:>8} {:>8}",
+            "GROUP", "N", "MEDIAN", "P85", "P95"
+        );
+
+        let mut by_project: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        let mut by_priority: BTreeMap<u8, Vec<i64>> = BTreeMap::new();
+        for record in records {
+            if let Some(value) = measure(&record.ticket) {
+                by_project.entry(&record.project).or_default().push(value);
+                by_priority
+                    .entry(record.ticket.priority as u8)
+                    .or_default()
+                    .push(value);
+            }
+        }
+
+        let rows = by_project
+            .into_iter()
+            .map(|(project, values)| (format!("project {project}"), values))
+            .chain(by_priority.into_iter().map(|(priority, values)| {
+                (format!("priority {:?}", Priority::from(priority)), values)
+            }));
+
+        for (group, values) in rows {
+            if let Some(summary) = summarize(values) {
+                let _ = writeln!(
+                    out,
+                    "{:<24} {:>5} {:>8.1} {:>8.1} {:>8.1}",
+                    group,
+                    summary.count,
+                    days(summary.median),
+                    days(summary.p85),
+                    days(summary.p95),
+                );
+            }
+        }
+    }
+
+    out
+}
+
+/// Write one row per completed ticket with its timestamps and durations
+pub fn write_csv(records: &[Record], writer: &mut impl Write) -> std::io::Result<()> {
+    writeln!(
+        writer,
+        "project,id,priority,created,started,done,lead_days,cycle_days"
+    )?;
+
+    for record in records {
+        let ticket = &record.ticket;
+        writeln!(
+            writer,
+            "{},{},{:?},{},{},{},{},{}",
+            csv_field(&record.project),
+            ticket.id,
+            ticket.priority,
+            history::format_timestamp(ticket.created_at),
+            ticket.started_at().map(history::format_timestamp).unwrap_or_default(),
+            ticket.done_at().map(history::format_timestamp).unwrap_or_default(),
+            ticket.lead_time().map(|t| format!("{:.2}", days(t))).unwrap_or_default(),
+            ticket.cycle_time().map(|t| format!("{:.2}", days(t))).unwrap_or_default(),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn days(seconds: i64) -> f64 {
+    seconds as f64 / 86400.0
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let summary = summarize((1..=20).rev().collect()).unwrap();
+        assert_eq!((summary.count, summary.median, summary.p85, summary.p95), (20, 10, 17, 19));
+        let single = summarize(vec![7]).unwrap();
+        assert_eq!((single.median, single.p95), (7, 7));
+        assert!(summarize(Vec::new()).is_none());
+    }
+}
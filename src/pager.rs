@@ -0,0 +1,160 @@
+use std::io::{IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::settings;
+use crate::verbosity::info;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// The running pager and a copy of the original stdout to restore
+static PAGER: Mutex<Option<(Child, i32)>> = Mutex::new(None);
+
+/// Remember --no-pager; must run before any command output
+pub fn init(no_pager: bool) {
+    DISABLED.store(no_pager, Ordering::Relaxed);
+}
+
+/// Restore the default SIGPIPE action, so output cut short by the pager or
+/// `| head` ends the process quietly instead of panicking. Only done once
+/// output is known to go there, since it also turns a failed write to a
+/// git child into a silent exit.
+fn end_on_closed_pipe() {
+    #[cfg(unix)]
+    // SAFETY: changes a signal disposition; no other thread handles SIGPIPE
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+    }
+}
+
+/// Pager command: TIX_PAGER, then core.pager, then PAGER, then less.
+///
+/// An empty value or "cat" turns paging off.
+fn command() -> Option<Vec<String>> {
+    let pager = std::env::var("TIX_PAGER")
+        .ok()
+        .or_else(|| settings::get().pager())
+        .or_else(|| std::env::var("PAGER").ok())
+        .unwrap_or_else(|| "less".to_string());
+
+    let parts: Vec<String> = pager.split_whitespace().map(str::to_string).collect();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+/// Pager command for `lines` of output, or None when it should be printed
+/// directly: with --no-pager, when stdout isn't a terminal, or when `lines`
+/// fits in the terminal.
+fn wanted(lines: usize) -> Option<Vec<String>> {
+    if DISABLED.load(Ordering::Relaxed) || !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let height = terminal_size::terminal_size()
+        .map(|(_, terminal_size::Height(h))| h as usize)
+        .or_else(|| std::env::var("LINES").ok()?.parse().ok())
+        .unwrap_or(24);
+    if lines < height {
+        return None;
+    }
+    command()
+}
+
+/// Send the rest of stdout through the pager when it won't fit on screen.
+///
+/// Only redirects stdout on unix; see `page` for output that is already
+/// known. Call `finish` before exiting.
+pub fn start(lines: usize) {
+    if !std::io::stdout().is_terminal() {
+        end_on_closed_pipe();
+    }
+    if let Some(parts) = wanted(lines) {
+        spawn(&parts);
+    }
+}
+
+/// Print `text`, through the pager when it won't fit on screen.
+///
+/// Unlike `start`, this also pages where stdout can't be redirected, by
+/// writing the text to the pager's input.
+pub fn page(text: &str) {
+    let lines = text.lines().count();
+    #[cfg(not(unix))]
+    if let Some(parts) = wanted(lines)
+        && pipe(&parts, text)
+    {
+        return;
+    }
+    start(lines);
+    print!("{text}");
+}
+
+fn pager(parts: &[String]) -> Command {
+    info!("paging through {}", parts.join(" "));
+    let mut command = Command::new(&parts[0]);
+    command.args(&parts[1..]).stdin(Stdio::piped());
+    // Same defaults as git: quit if one screen, keep colours, don't clear
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    command
+}
+
+#[cfg(unix)]
+fn spawn(parts: &[String]) {
+    use std::os::fd::AsRawFd;
+
+    let Ok(mut child) = pager(parts).spawn() else {
+        return;
+    };
+    let Some(stdin) = child.stdin.take() else {
+        return;
+    };
+
+    let _ = std::io::stdout().flush();
+    // SAFETY: plain fd juggling on descriptors this process owns
+    let saved = unsafe {
+        let saved = libc::dup(libc::STDOUT_FILENO);
+        if saved < 0 || libc::dup2(stdin.as_raw_fd(), libc::STDOUT_FILENO) < 0 {
+            return;
+        }
+        saved
+    };
+    end_on_closed_pipe();
+    *PAGER.lock().unwrap_or_else(|e| e.into_inner()) = Some((child, saved));
+}
+
+#[cfg(not(unix))]
+fn spawn(_parts: &[String]) {}
+
+/// Run the pager on `text` and wait for it; false if it couldn't start
+#[cfg(not(unix))]
+fn pipe(parts: &[String], text: &str) -> bool {
+    let Ok(mut child) = pager(parts).spawn() else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
+
+/// Close the pager's input and wait for the user to quit it
+pub fn finish() {
+    let Some((mut child, saved)) = PAGER.lock().unwrap_or_else(|e| e.into_inner()).take() else {
+        return;
+    };
+    let _ = std::io::stdout().flush();
+    #[cfg(unix)]
+    // SAFETY: restores the descriptor saved in `spawn`, closing the pipe
+    unsafe {
+        libc::dup2(saved, libc::STDOUT_FILENO);
+        libc::close(saved);
+    }
+    #[cfg(not(unix))]
+    let _ = saved;
+    let _ = child.wait();
+}
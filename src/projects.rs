@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::ffi::TixError;
+use crate::git::{self, GitError};
+
+/// The project tix creates on init; the core library relies on it existing
+const MAIN_PROJECT: &str = "main";
+
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error(transparent)]
+    TixError(#[from] TixError),
+
+    #[error(transparent)]
+    GitError(#[from] GitError),
+
+    #[error("Cannot {0} the main project")]
+    MainProject(&'static str),
+
+    #[error("Cannot delete the current project '{0}', switch to another project first")]
+    CurrentProject(String),
+}
+
+/// Description and archive state kept in the ticket repository's git config
+#[derive(Default)]
+pub struct ProjectInfo {
+    pub description: Option<String>,
+    pub archived: bool,
+}
+
+/// Read description and archive state of every project
+pub fn info() -> Result<HashMap<String, ProjectInfo>, GitError> {
+    let mut projects: HashMap<String, ProjectInfo> = HashMap::new();
+
+    // NUL-separated "key\nvalue" entries, so descriptions may span lines
+    let output = match git::git(&[
+        "config",
+        "-z",
+        "--get-regexp",
+        r"^branch\..*\.(description|archived)$",
+    ]) {
+        Ok(output) => output,
+        // Exit status 1 means no keys match
+        Err(GitError::Failed { code: Some(1), .. }) => String::new(),
+        Err(err) => return Err(err),
+    };
+
+    for entry in output.split('\0') {
+        let Some((key, value)) = entry.split_once('\n') else {
+            continue;
+        };
+        let Some(key) = key.strip_prefix("branch.") else {
+            continue;
+        };
+        if let Some(project) = key.strip_suffix(".description") {
+            projects.entry(project.to_string()).or_default().description =
+                Some(value.to_string());
+        } else if let Some(project) = key.strip_suffix(".archived") {
+            projects.entry(project.to_string()).or_default().archived = value == "true";
+        }
+    }
+
+    Ok(projects)
+}
+
+pub fn rename(project: &str, new_name: &str) -> Result<String, ProjectError> {
+    if project == MAIN_PROJECT {
+        return Err(ProjectError::MainProject("rename"));
+    }
+    git::ensure_project(project)?;
+    if git::ensure_project(new_name).is_ok() {
+        return Err(TixError::SwitchProjectAlreadyExists(new_name.to_string()).into());
+    }
+
+    // Also moves the branch config, so description and archive state follow
+    git::git(&["branch", "-m", project, new_name])?;
+    Ok(format!("Renamed project {project} to {new_name}"))
+}
+
+pub fn delete(project: &str) -> Result<String, ProjectError> {
+    if project == MAIN_PROJECT {
+        return Err(ProjectError::MainProject("delete"));
+    }
+    git::ensure_project(project)?;
+    if git::current_branch()? == project {
+        return Err(ProjectError::CurrentProject(project.to_string()));
+    }
+
+    git::git(&["branch", "-D", project])?;
+    Ok(format!("Deleted project {project}"))
+}
+
+pub fn archive(project: &str, archived: bool) -> Result<String, ProjectError> {
+    if project == MAIN_PROJECT {
+        return Err(ProjectError::MainProject("archive"));
+    }
+    git::ensure_project(project)?;
+
+    let key = format!("branch.{project}.archived");
+    if archived {
+        git::git(&["config", &key, "true"])?;
+        Ok(format!("Archived project {project}"))
+    } else {
+        // Exits non-zero if the project wasn't archived, which is fine
+        git::git(&["config", "--unset", &key]).ok();
+        Ok(format!("Unarchived project {project}"))
+    }
+}
+
+/// Set the description of a project, or clear it when empty
+pub fn describe(project: &str, description: &str) -> Result<String, ProjectError> {
+    git::ensure_project(project)?;
+
+    let key = format!("branch.{project}.description");
+    if description.trim().is_empty() {
+        git::git(&["config", "--unset", &key]).ok();
+        Ok(format!("Cleared description of project {project}"))
+    } else {
+        git::git(&["config", &key, description.trim()])?;
+        Ok(format!("Updated description of project {project}"))
+    }
+}
@@ -0,0 +1,25 @@
+use std::io::{BufRead, Write};
+
+/// Ask a yes/no question on stderr; anything but "y" or "yes" means no
+pub fn confirm(question: &str) -> std::io::Result<bool> {
+    eprint!("{question} [y/N] ");
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(
+        answer.trim().to_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Ask a question on stderr and return the trimmed answer
+pub fn ask(question: &str) -> std::io::Result<String> {
+    eprint!("{question} ");
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().to_string())
+}
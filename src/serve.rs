@@ -0,0 +1,643 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use thiserror::Error;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::attrs::{self, Extras};
+use crate::branch;
+use crate::exit::{self, ErrorKind};
+use crate::export::{self, Item};
+use crate::ffi::{self, Priority, Status, Ticket, TixError};
+use crate::git;
+use crate::history;
+use crate::projects;
+use crate::verbosity::info;
+
+/// How often the project branches are checked for changes to announce
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Idle event streams get a comment this often, which also notices
+/// clients that went away
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Largest request body accepted
+const MAX_BODY: u64 = 1024 * 1024;
+
+const ALLOWED_METHODS: &str = "GET, POST, PATCH, OPTIONS";
+
+#[derive(Debug, Error)]
+pub enum ServeError {
+    #[error("Failed to listen on {0}: {1}")]
+    Bind(String, String),
+}
+
+/// Errors of the HTTP layer itself, as opposed to those of tix
+#[derive(Debug, Error)]
+enum RequestError {
+    #[error("{0}")]
+    BadRequest(String),
+
+    #[error("Missing or wrong token")]
+    Unauthorized,
+
+    #[error("Origin {0} is not allowed")]
+    ForbiddenOrigin(String),
+
+    #[error("No such endpoint")]
+    NotFound,
+
+    #[error("Method not allowed")]
+    MethodNotAllowed,
+
+    #[error("Request body must be application/json")]
+    UnsupportedMediaType,
+}
+
+struct State {
+    token: Option<String>,
+    /// Browser origins allowed to call the API, `*` for any
+    origins: Vec<String>,
+    /// Held around every call into the tix library, which isn't reentrant,
+    /// and every git read, so requests never see a half-made change
+    library: Mutex<()>,
+    /// Open event streams
+    subscribers: Mutex<Vec<Sender<String>>>,
+}
+
+impl State {
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.library.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Start listening on `bind`, e.g. 127.0.0.1:7777
+pub fn bind(bind: &str) -> Result<Server, ServeError> {
+    Server::http(bind).map_err(|err| ServeError::Bind(bind.to_string(), err.to_string()))
+}
+
+/// Answer requests until the process is stopped, each on its own thread.
+/// With a token, every request must carry it as `Authorization: Bearer`
+/// or, for browsers' EventSource, as `?token=`. Pages served from one of
+/// `origins` may call the API from a browser.
+pub fn run(server: Server, token: Option<String>, origins: Vec<String>) {
+    let state = Arc::new(State {
+        token,
+        origins,
+        library: Mutex::new(()),
+        subscribers: Mutex::new(Vec::new()),
+    });
+
+    let watcher = Arc::clone(&state);
+    std::thread::spawn(move || watch(&watcher));
+
+    for request in server.incoming_requests() {
+        let state = Arc::clone(&state);
+        std::thread::spawn(move || handle(&state, request));
+    }
+}
+
+fn handle(state: &Arc<State>, mut request: Request) {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let query: HashMap<String, String> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('=').or(Some((pair, ""))))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (decode(key), decode(value)))
+        .collect();
+    let segments: Vec<String> = path.split('/').filter(|s| !s.is_empty()).map(decode).collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let method = request.method().clone();
+    let cors = cors_headers(state, &request);
+
+    // Browsers ask before sending a cross-origin request with a token or
+    // JSON body, and never send the token with that question
+    if method == Method::Options {
+        info!("{method} {path} -> 204");
+        let mut response = Response::empty(204).with_header(header("Allow", ALLOWED_METHODS));
+        for header in cors {
+            response.add_header(header);
+        }
+        request.respond(response).ok();
+        return;
+    }
+
+    // Pages on other origins can still send simple requests, which the
+    // CORS headers only keep them from reading the answer to
+    let result = if let Some(origin) = origin(&request).filter(|origin| !origin_allowed(state, origin)) {
+        Err(RequestError::ForbiddenOrigin(origin.to_string()).into())
+    } else if !authorized(state, &request, &query) {
+        Err(RequestError::Unauthorized.into())
+    } else {
+        match (&method, segments.as_slice()) {
+            (Method::Get, ["events"]) => {
+                info!("{method} {path} -> stream");
+                return events(state, request, &cors);
+            }
+            (Method::Get, ["projects"]) => list_projects(state),
+            (Method::Get, ["tickets"]) => list_tickets(state, &query),
+            (Method::Post, ["tickets"]) => {
+                read_json(&mut request).and_then(|body| create_ticket(state, body, &query))
+            }
+            (Method::Get, ["tickets", id]) => show_ticket(state, id, &query),
+            (Method::Patch, ["tickets", id]) => {
+                read_json(&mut request).and_then(|body| update_ticket(state, id, body, &query))
+            }
+            (Method::Get, ["history"]) => list_history(state, &query),
+            (_, ["projects" | "tickets" | "history" | "events"] | ["tickets", _]) => {
+                Err(RequestError::MethodNotAllowed.into())
+            }
+            _ => Err(RequestError::NotFound.into()),
+        }
+    };
+
+    let (status, body) = result.unwrap_or_else(|err| error_body(&err));
+    info!("{method} {path} -> {status}");
+    let mut response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"));
+    for header in cors {
+        response.add_header(header);
+    }
+    request.respond(response).ok();
+}
+
+/// The page a browser request comes from; None for other clients
+fn origin(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Origin"))
+        .map(|h| h.value.as_str())
+}
+
+fn origin_allowed(state: &State, origin: &str) -> bool {
+    state.origins.iter().any(|o| o == "*" || o == origin)
+}
+
+/// CORS headers for a request from a browser page on an allowed origin
+fn cors_headers(state: &State, request: &Request) -> Vec<Header> {
+    let allowed = if state.origins.iter().any(|o| o == "*") {
+        "*"
+    } else {
+        match origin(request) {
+            Some(origin) if origin_allowed(state, origin) => origin,
+            _ => return Vec::new(),
+        }
+    };
+    vec![
+        header("Access-Control-Allow-Origin", allowed),
+        header("Access-Control-Allow-Methods", ALLOWED_METHODS),
+        header("Access-Control-Allow-Headers", "Authorization, Content-Type"),
+        header("Access-Control-Max-Age", "600"),
+        header("Vary", "Origin"),
+    ]
+}
+
+fn authorized(state: &State, request: &Request, query: &HashMap<String, String>) -> bool {
+    let Some(token) = &state.token else {
+        return true;
+    };
+    let bearer = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .and_then(|h| h.value.as_str().strip_prefix("Bearer "));
+    bearer
+        .or(query.get("token").map(String::as_str))
+        .is_some_and(|given| same(given.trim(), token))
+}
+
+/// Compare without stopping at the first difference, so response times
+/// don't give the token away
+fn same(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Status and JSON body of an error, in the shape of `--error-format json`
+fn error_body(err: &anyhow::Error) -> (u16, Value) {
+    if let Some(err) = err.downcast_ref::<RequestError>() {
+        let (status, kind) = match err {
+            RequestError::BadRequest(_) => (400, "bad_request"),
+            RequestError::Unauthorized => (401, "unauthorized"),
+            RequestError::ForbiddenOrigin(_) => (403, "forbidden"),
+            RequestError::NotFound => (404, "not_found"),
+            RequestError::MethodNotAllowed => (405, "method_not_allowed"),
+            RequestError::UnsupportedMediaType => (415, "unsupported_media_type"),
+        };
+        return (status, json!({ "kind": kind, "message": err.to_string() }));
+    }
+
+    let kind = exit::kind(err);
+    let status = match kind {
+        ErrorKind::TicketNotFound | ErrorKind::ProjectNotFound => 404,
+        ErrorKind::InvalidTicketId
+        | ErrorKind::InvalidPriority
+        | ErrorKind::InvalidStatus
+        | ErrorKind::InvalidTitle
+        | ErrorKind::InvalidArgument => 400,
+        ErrorKind::TicketAlreadyExists | ErrorKind::ProjectAlreadyExists => 409,
+        _ => 500,
+    };
+    (status, json!({ "code": kind.code(), "kind": kind.name(), "message": err.to_string() }))
+}
+
+fn list_projects(state: &State) -> anyhow::Result<(u16, Value)> {
+    let _library = state.lock();
+    let current = git::current_branch()?;
+    let info = projects::info()?;
+
+    let mut list = Vec::new();
+    for name in ffi::projects()? {
+        let project = info.get(&name);
+        list.push(json!({
+            "name": name,
+            "current": name == current,
+            "description": project.and_then(|p| p.description.clone()),
+            "archived": project.is_some_and(|p| p.archived),
+            "tickets": branch::tickets(&name)?.len(),
+        }));
+    }
+    Ok((200, Value::Array(list)))
+}
+
+/// `?project=` names the project to read, the current one by default
+fn project(query: &HashMap<String, String>) -> anyhow::Result<String> {
+    match query.get("project") {
+        Some(project) => {
+            git::ensure_project(project)?;
+            Ok(project.clone())
+        }
+        None => Ok(git::current_branch()?),
+    }
+}
+
+/// The current project, which the library changes tickets in. A
+/// `?project=` naming any other is refused rather than silently ignored.
+fn current_project(query: &HashMap<String, String>) -> anyhow::Result<String> {
+    let current = git::current_branch()?;
+    match query.get("project") {
+        Some(project) if *project != current => Err(RequestError::BadRequest(format!(
+            "tickets can only be changed in the current project '{current}', not '{project}'"
+        ))
+        .into()),
+        _ => Ok(current),
+    }
+}
+
+/// Values of a comma-separated filter such as `?status=todo,doing`
+fn filter<T: ValueEnum>(query: &HashMap<String, String>, key: &str) -> anyhow::Result<Vec<T>> {
+    let Some(values) = query.get(key) else {
+        return Ok(Vec::new());
+    };
+    values
+        .split(',')
+        .map(|value| {
+            T::from_str(value.trim(), true).map_err(|err| RequestError::BadRequest(format!("{key}: {err}")).into())
+        })
+        .collect()
+}
+
+fn list_tickets(state: &State, query: &HashMap<String, String>) -> anyhow::Result<(u16, Value)> {
+    let statuses: Vec<Status> = filter(query, "status")?;
+    let priorities: Vec<Priority> = filter(query, "priority")?;
+
+    let _library = state.lock();
+    let project = project(query)?;
+    let mut extras = attrs::read(&project)?;
+    let mut items: Vec<Item> = branch::tickets(&project)?
+        .into_iter()
+        .filter(|t| statuses.is_empty() || statuses.contains(&t.status))
+        .filter(|t| priorities.is_empty() || priorities.contains(&t.priority))
+        .map(|ticket| Item {
+            extras: extras.remove(&ticket.id).unwrap_or_default(),
+            project: project.clone(),
+            ticket,
+        })
+        .collect();
+    items.sort_by_key(export::sort_key);
+
+    let list = items.iter().map(|item| ticket_json(&item.project, &item.ticket, &item.extras)).collect();
+    Ok((200, Value::Array(list)))
+}
+
+fn show_ticket(state: &State, id: &str, query: &HashMap<String, String>) -> anyhow::Result<(u16, Value)> {
+    let _library = state.lock();
+    let project = project(query)?;
+    Ok((200, find(&project, id)?))
+}
+
+/// A ticket with its labels and attributes, or TicketNotFound
+fn find(project: &str, id: &str) -> anyhow::Result<Value> {
+    let ticket = branch::find(project, id)?.ok_or(TixError::TicketNotFound)?;
+    let extras = attrs::read(project)?.remove(id).unwrap_or_default();
+    Ok(ticket_json(project, &ticket, &extras))
+}
+
+fn ticket_json(project: &str, ticket: &Ticket, extras: &Extras) -> Value {
+    json!({
+        "id": ticket.id,
+        "project": project,
+        "title": ticket.title,
+        "body": ticket.body,
+        "status": format!("{:?}", ticket.status),
+        "priority": format!("{:?}", ticket.priority),
+        "created": history::ticket_created(&ticket.id).map(history::format_timestamp),
+        "labels": extras.labels,
+        "attributes": extras.attributes,
+    })
+}
+
+/// Fields of POST /tickets; new tickets go to the current project
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewTicket {
+    title: String,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+}
+
+/// Fields of PATCH /tickets/<id>; those left out stay as they are
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TicketChanges {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+}
+
+fn parse<T: ValueEnum>(field: &str, value: Option<&str>) -> anyhow::Result<Option<T>> {
+    value
+        .map(|value| T::from_str(value, true).map_err(|err| RequestError::BadRequest(format!("{field}: {err}")).into()))
+        .transpose()
+}
+
+fn create_ticket(state: &State, body: Value, query: &HashMap<String, String>) -> anyhow::Result<(u16, Value)> {
+    let new: NewTicket = serde_json::from_value(body).map_err(|err| RequestError::BadRequest(err.to_string()))?;
+    if new.title.trim().is_empty() {
+        return Err(RequestError::BadRequest("title must not be empty".to_string()).into());
+    }
+    let priority = parse("priority", new.priority.as_deref())?.unwrap_or(Priority::z);
+    let status = parse("status", new.status.as_deref())?;
+
+    let _library = state.lock();
+    let project = current_project(query)?;
+    let id = ffi::add(new.title.trim(), new.body.as_deref(), priority, status)?;
+    Ok((201, find(&project, &id)?))
+}
+
+fn update_ticket(
+    state: &State,
+    id: &str,
+    body: Value,
+    query: &HashMap<String, String>,
+) -> anyhow::Result<(u16, Value)> {
+    let changes: TicketChanges =
+        serde_json::from_value(body).map_err(|err| RequestError::BadRequest(err.to_string()))?;
+    if changes.title.as_deref().is_some_and(|title| title.trim().is_empty()) {
+        return Err(RequestError::BadRequest("title must not be empty".to_string()).into());
+    }
+    let priority = parse("priority", changes.priority.as_deref())?;
+    let status: Option<Status> = parse("status", changes.status.as_deref())?;
+
+    let _library = state.lock();
+    let project = current_project(query)?;
+    let ticket = branch::find(&project, id)?.ok_or(TixError::TicketNotFound)?;
+
+    // Fields and status are separate library calls, squashed into one
+    // commit so the update applies, and undoes, as a whole
+    let base = git::head()?;
+    let result = (|| -> anyhow::Result<()> {
+        if changes.title.is_some() || changes.body.is_some() || priority.is_some() {
+            ffi::amend(id, changes.title.as_deref().map(str::trim), changes.body.as_deref(), priority)?;
+        }
+        if let Some(status) = status.filter(|status| *status != ticket.status) {
+            ffi::mv(id, status)?;
+        }
+        Ok(())
+    })();
+    if let Err(err) = result {
+        git::rollback(&base)?;
+        return Err(err);
+    }
+    git::squash(&base, &format!("amend {id}"))?;
+    Ok((200, find(&project, id)?))
+}
+
+/// Commits newest first, `?limit=` of them (50 by default)
+fn list_history(state: &State, query: &HashMap<String, String>) -> anyhow::Result<(u16, Value)> {
+    let limit = match query.get("limit") {
+        Some(limit) => limit
+            .parse()
+            .map_err(|_| RequestError::BadRequest(format!("limit: invalid number '{limit}'")))?,
+        None => 50,
+    };
+
+    let _library = state.lock();
+    let project = project(query)?;
+    let commits = history::commits(&project)?;
+    let list = commits
+        .iter()
+        .rev()
+        .take(limit)
+        .map(|commit| {
+            let changes: Vec<Value> = commit
+                .changes
+                .iter()
+                .map(|c| json!({ "ticket": c.ticket_id, "file": c.file, "added": c.added }))
+                .collect();
+            json!({
+                "commit": commit.hash,
+                "timestamp": history::format_timestamp(commit.timestamp),
+                "author": commit.author,
+                "changes": changes,
+            })
+        })
+        .collect();
+    Ok((200, Value::Array(list)))
+}
+
+/// Read a JSON body. Other content types are refused, since a browser only
+/// sends JSON cross-origin after asking with a preflight request.
+fn read_json(request: &mut Request) -> anyhow::Result<Value> {
+    let is_json = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Content-Type"))
+        .and_then(|h| h.value.as_str().split(';').next())
+        .is_some_and(|media| media.trim().eq_ignore_ascii_case("application/json"));
+    if !is_json {
+        return Err(RequestError::UnsupportedMediaType.into());
+    }
+    let mut text = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut text)
+        .map_err(|err| RequestError::BadRequest(format!("cannot read body: {err}")))?;
+    if text.len() as u64 > MAX_BODY {
+        return Err(RequestError::BadRequest(format!("body is larger than {MAX_BODY} bytes")).into());
+    }
+    Ok(serde_json::from_str(&text).map_err(|err| RequestError::BadRequest(format!("invalid JSON: {err}")))?)
+}
+
+/// Stream Server-Sent Events to the client until it goes away. The
+/// response is written by hand, since tiny_http holds chunked bodies back
+/// until 8 KiB have gathered.
+fn events(state: &State, request: Request, cors: &[Header]) {
+    let (sender, receiver) = mpsc::channel();
+    state
+        .subscribers
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(sender);
+
+    let mut writer = request.into_writer();
+    let mut head = String::from("HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n");
+    for header in cors {
+        head.push_str(&format!("{}: {}\r\n", header.field, header.value));
+    }
+    head.push_str("Connection: close\r\n\r\n: connected\n\n");
+    if writer.write_all(head.as_bytes()).and_then(|()| writer.flush()).is_err() {
+        return;
+    }
+    loop {
+        let message = match receiver.recv_timeout(KEEPALIVE_INTERVAL) {
+            Ok(event) => format!("event: change\ndata: {event}\n\n"),
+            Err(RecvTimeoutError::Timeout) => ": keepalive\n\n".to_string(),
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        if writer.write_all(message.as_bytes()).and_then(|()| writer.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Poll the project branches and announce every commit, whether made
+/// through the API or by tix elsewhere, to the event streams
+fn watch(state: &State) {
+    let mut known = heads(state).unwrap_or_default();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let Ok(current) = heads(state) else {
+            continue;
+        };
+        if current == known {
+            continue;
+        }
+
+        let mut events = Vec::new();
+        for (project, head) in &current {
+            match known.get(project) {
+                Some(old) if old == head => {}
+                old => events.push(json!({
+                    "project": project,
+                    "commit": head,
+                    "tickets": old.map(|old| changed_tickets(state, old, head)).unwrap_or_default(),
+                })),
+            }
+        }
+        for project in known.keys().filter(|project| !current.contains_key(*project)) {
+            events.push(json!({ "project": project, "commit": null, "tickets": [] }));
+        }
+        known = current;
+
+        let mut subscribers = state.subscribers.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for event in events {
+            let event = event.to_string();
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+/// Head commit of every project branch
+fn heads(state: &State) -> Result<BTreeMap<String, String>, git::GitError> {
+    let _library = state.lock();
+    let output = git::git(&["for-each-ref", "--format=%(refname:short) %(objectname)", "refs/heads"])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(project, head)| (project.to_string(), head.to_string()))
+        .collect())
+}
+
+/// Tickets whose files differ between two commits
+fn changed_tickets(state: &State, old: &str, new: &str) -> Vec<String> {
+    let _library = state.lock();
+    let output = git::git(&["diff", "--name-only", old, new]).unwrap_or_default();
+    let mut tickets: Vec<String> = output
+        .lines()
+        .filter_map(|path| path.split_once('/').map(|(id, _)| id))
+        .filter(|id| history::is_ticket_id(id))
+        .map(str::to_string)
+        .collect();
+    tickets.dedup();
+    tickets
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field.as_bytes(), value.as_bytes()).expect("static header is valid")
+}
+
+/// Undo percent-encoding, with `+` as a space as in form data
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => match std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    out.push(byte);
+                    i += 3;
+                    continue;
+                }
+                None => out.push(b'%'),
+            },
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_percent_and_plus() {
+        assert_eq!(decode("todo%2Cdoing"), "todo,doing");
+        assert_eq!(decode("a+b%20c"), "a b c");
+        assert_eq!(decode("caf%C3%A9"), "café");
+        assert_eq!(decode("plain"), "plain");
+    }
+
+    #[test]
+    fn decode_keeps_broken_escapes() {
+        assert_eq!(decode("100%"), "100%");
+        assert_eq!(decode("%zz1"), "%zz1");
+        assert_eq!(decode("%4"), "%4");
+        assert_eq!(decode("%%41"), "%A");
+    }
+}
@@ -0,0 +1,393 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use chrono::format::{Item, StrftimeItems};
+use clap::ValueEnum;
+use thiserror::Error;
+use toml::{Table, Value};
+
+use crate::color::{self, ColorChoice};
+use crate::ffi::{Priority, Status};
+use crate::verbosity::info;
+use crate::workspace;
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Failed to read {0}: {1}")]
+    Io(PathBuf, std::io::Error),
+
+    #[error("Invalid config file {0}: {1}")]
+    Parse(PathBuf, toml::de::Error),
+
+    #[error("Unknown CLI configuration key '{0}'")]
+    UnknownKey(String),
+
+    #[error("Invalid value '{value}' for {key}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("Not in a tix workspace, use --global")]
+    NoWorkspace,
+
+    #[error("Cannot find the global config directory, set HOME or XDG_CONFIG_HOME")]
+    NoGlobalPath,
+}
+
+/// Keys understood by the CLI, as opposed to the core library's keys
+pub const KEYS: &[&str] = &[
+    "ls.status",
+    "add.status",
+    "add.priority",
+    "core.editor",
+    "core.pager",
+    "core.color",
+    "core.date-format",
+];
+
+/// Which config file to read from or write to
+#[derive(Debug, Clone, Copy)]
+pub enum Scope {
+    Global,
+    Workspace,
+}
+
+/// CLI preferences layered from the global and the workspace config file
+pub struct Settings {
+    global: Table,
+    workspace: Table,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Load both config files; must run after the workspace is resolved
+pub fn init() -> Result<(), SettingsError> {
+    let settings = load(workspace::root())?;
+    SETTINGS.get_or_init(|| settings);
+    Ok(())
+}
+
+/// Read the global file and the file of the workspace rooted at `root`
+pub fn load(root: Option<&Path>) -> Result<Settings, SettingsError> {
+    Ok(Settings {
+        global: read(global_path().as_ref())?,
+        workspace: read(root.map(workspace_file).as_ref())?,
+    })
+}
+
+/// The loaded settings, or empty settings if `init` was not called
+pub fn get() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings {
+        global: Table::new(),
+        workspace: Table::new(),
+    })
+}
+
+/// Whether a key belongs to the CLI rather than the core library
+pub fn is_cli_key(key: &str) -> bool {
+    KEYS.contains(&key)
+        || color::KEYS.contains(&key)
+        || key.strip_prefix("alias.").is_some_and(is_alias_name)
+}
+
+fn is_alias_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('-') && !name.contains(['.', ' '])
+}
+
+/// `$XDG_CONFIG_HOME/tix/config.toml`, falling back to `~/.config/tix/config.toml`
+pub fn global_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("tix").join("config.toml"))
+}
+
+/// The workspace file of the current workspace, if there is one
+pub fn workspace_path() -> Option<PathBuf> {
+    workspace::root().map(workspace_file)
+}
+
+/// Kept in the ticket repository's git directory so tix never commits it
+fn workspace_file(root: &Path) -> PathBuf {
+    root.join(workspace::TIX_DIR_NAME)
+        .join(".git")
+        .join("cli.toml")
+}
+
+#[cfg(test)]
+impl Settings {
+    /// Settings with only a workspace file holding `toml`
+    pub fn from_toml(toml: &str) -> Settings {
+        Settings {
+            global: Table::new(),
+            workspace: toml.parse().expect("valid TOML"),
+        }
+    }
+}
+
+impl Settings {
+    /// Raw value of a dotted key; the workspace file wins over the global one
+    pub fn value(&self, key: &str) -> Option<&Value> {
+        lookup(&self.workspace, key).or_else(|| lookup(&self.global, key))
+    }
+
+    /// Value of a dotted key as a string
+    pub fn string(&self, key: &str) -> Option<String> {
+        match self.value(key)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Array(items) => Some(
+                items
+                    .iter()
+                    .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// Statuses `tix ls` shows when none are given
+    pub fn list_statuses(&self) -> Vec<Status> {
+        self.string("ls.status")
+            .and_then(|value| parse_list::<Status>(&value).ok())
+            .filter(|statuses| !statuses.is_empty())
+            .unwrap_or_else(|| vec![Status::todo, Status::doing])
+    }
+
+    pub fn add_status(&self) -> Option<Status> {
+        self.string("add.status")
+            .and_then(|value| Status::from_str(&value, true).ok())
+    }
+
+    pub fn add_priority(&self) -> Option<Priority> {
+        self.string("add.priority")
+            .and_then(|value| Priority::from_str(&value, true).ok())
+    }
+
+    pub fn editor(&self) -> Option<String> {
+        self.string("core.editor")
+    }
+
+    pub fn pager(&self) -> Option<String> {
+        self.string("core.pager")
+    }
+
+    pub fn color(&self) -> Option<ColorChoice> {
+        self.string("core.color")
+            .and_then(|value| ColorChoice::from_str(&value, true).ok())
+    }
+
+    /// Style configured for a colour key such as `color.priority.a`
+    pub fn style(&self, key: &str) -> Option<String> {
+        self.string(key)
+    }
+
+    /// Expansion of a command alias
+    pub fn alias(&self, name: &str) -> Option<String> {
+        self.string(&format!("alias.{name}"))
+    }
+
+    /// All aliases, the workspace file overriding the global one
+    pub fn aliases(&self) -> Vec<(String, String)> {
+        self.entries(None)
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.strip_prefix("alias.")?.to_string(), value)))
+            .collect()
+    }
+
+    pub fn date_format(&self) -> String {
+        self.string("core.date-format")
+            .unwrap_or_else(|| "%Y-%m-%d".to_string())
+    }
+
+    /// Every key set in the given scope, or in either file when None
+    pub fn entries(&self, scope: Option<Scope>) -> Vec<(String, String)> {
+        let mut keys = Vec::new();
+        match scope {
+            Some(Scope::Global) => flatten(&self.global, "", &mut keys),
+            Some(Scope::Workspace) => flatten(&self.workspace, "", &mut keys),
+            None => {
+                flatten(&self.global, "", &mut keys);
+                flatten(&self.workspace, "", &mut keys);
+            }
+        }
+        keys.sort();
+        keys.dedup();
+
+        keys.into_iter()
+            .filter_map(|key| {
+                let table = match scope {
+                    Some(Scope::Global) => &self.global,
+                    Some(Scope::Workspace) => &self.workspace,
+                    None => return self.string(&key).map(|value| (key.clone(), value)),
+                };
+                let value = match lookup(table, &key)? {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                Some((key, value))
+            })
+            .collect()
+    }
+}
+
+/// Validate and store a key in the chosen config file
+pub fn set(scope: Scope, key: &str, value: &str) -> Result<(), SettingsError> {
+    validate(key, value)?;
+    update(scope, |table| insert(table, key, Value::String(value.to_string())))
+}
+
+/// Remove a key from the chosen config file
+pub fn unset(scope: Scope, key: &str) -> Result<(), SettingsError> {
+    if !is_cli_key(key) {
+        return Err(SettingsError::UnknownKey(key.to_string()));
+    }
+    update(scope, |table| remove(table, key))
+}
+
+fn validate(key: &str, value: &str) -> Result<(), SettingsError> {
+    let invalid = |reason: String| SettingsError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        reason,
+    };
+
+    match key {
+        "ls.status" => parse_list::<Status>(value).map(|_| ()).map_err(invalid),
+        "add.status" => Status::from_str(value, true).map(|_| ()).map_err(invalid),
+        "add.priority" => Priority::from_str(value, true).map(|_| ()).map_err(invalid),
+        "core.color" => ColorChoice::from_str(value, true).map(|_| ()).map_err(invalid),
+        "core.date-format" => match StrftimeItems::new(value).any(|item| matches!(item, Item::Error)) {
+            true => Err(invalid("not a valid strftime format".to_string())),
+            false => Ok(()),
+        },
+        key if key.starts_with("color.") => color::parse_style(value).map(|_| ()).map_err(invalid),
+        key if key.starts_with("alias.") && is_cli_key(key) => match value.trim() {
+            "" => Err(invalid("an alias needs a command".to_string())),
+            _ => Ok(()),
+        },
+        key if is_cli_key(key) => Ok(()),
+        key => Err(SettingsError::UnknownKey(key.to_string())),
+    }
+}
+
+fn parse_list<T: ValueEnum>(value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| T::from_str(item, true))
+        .collect()
+}
+
+fn path_for(scope: Scope) -> Result<PathBuf, SettingsError> {
+    match scope {
+        Scope::Global => global_path().ok_or(SettingsError::NoGlobalPath),
+        Scope::Workspace => workspace_path().ok_or(SettingsError::NoWorkspace),
+    }
+}
+
+fn read(path: Option<&PathBuf>) -> Result<Table, SettingsError> {
+    let Some(path) = path else {
+        return Ok(Table::new());
+    };
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            info!("reading config {}", path.display());
+            content
+                .parse::<Table>()
+                .map_err(|err| SettingsError::Parse(path.clone(), err))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Table::new()),
+        Err(err) => Err(SettingsError::Io(path.clone(), err)),
+    }
+}
+
+fn update(scope: Scope, edit: impl FnOnce(&mut Table)) -> Result<(), SettingsError> {
+    let path = path_for(scope)?;
+    let mut table = read(Some(&path))?;
+    edit(&mut table);
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| SettingsError::Io(path.clone(), err))?;
+    }
+    std::fs::write(&path, table.to_string()).map_err(|err| SettingsError::Io(path.clone(), err))
+}
+
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (path, name) = key.rsplit_once('.')?;
+    let mut table = table;
+    for section in path.split('.') {
+        table = table.get(section)?.as_table()?;
+    }
+    table.get(name)
+}
+
+fn insert(table: &mut Table, key: &str, value: Value) {
+    let Some((path, name)) = key.rsplit_once('.') else {
+        return;
+    };
+    let mut table = table;
+    for section in path.split('.') {
+        let entry = table
+            .entry(section)
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+        let Value::Table(inner) = entry else {
+            return;
+        };
+        table = inner;
+    }
+    table.insert(name.to_string(), value);
+}
+
+/// Remove a dotted key and any sections left empty by it
+fn remove(table: &mut Table, key: &str) {
+    match key.split_once('.') {
+        None => {
+            table.remove(key);
+        }
+        Some((section, rest)) => {
+            if let Some(Value::Table(inner)) = table.get_mut(section) {
+                remove(inner, rest);
+                if inner.is_empty() {
+                    table.remove(section);
+                }
+            }
+        }
+    }
+}
+
+fn flatten(table: &Table, prefix: &str, keys: &mut Vec<String>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        match value {
+            Value::Table(inner) => flatten(inner, &key, keys),
+            _ => keys.push(key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_date_format() {
+        assert!(validate("core.date-format", "%d %b").is_ok());
+        assert!(matches!(validate("core.date-format", "%Q"), Err(SettingsError::InvalidValue { .. })));
+    }
+}
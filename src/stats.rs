@@ -0,0 +1,185 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+
+use strum::IntoEnumIterator;
+
+use crate::ffi::{Priority, Status};
+use crate::git::{self, GitError};
+use crate::history::{self, TicketHistory};
+
+const PRIORITIES: [Priority; 4] = [Priority::a, Priority::b, Priority::c, Priority::z];
+const DAY: i64 = 86400;
+const OLDEST_LIMIT: usize = 5;
+
+/// Per-author activity over the history of the selected projects
+#[derive(Default)]
+pub struct Activity {
+    pub commits: usize,
+    pub commits_30d: usize,
+    pub created: usize,
+}
+
+pub struct Stats {
+    pub projects: Vec<String>,
+    /// Tickets with the project they live on, deduplicated by ID
+    pub tickets: Vec<(String, TicketHistory)>,
+    pub authors: BTreeMap<String, Activity>,
+}
+
+/// Gather ticket lifecycles and author activity for the given projects
+pub fn collect(projects: Vec<String>) -> Result<Stats, GitError> {
+    let now = history::now();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut tickets: Vec<(String, TicketHistory)> = Vec::new();
+    let mut authors: BTreeMap<String, Activity> = BTreeMap::new();
+    let mut seen_commits = HashSet::new();
+
+    for project in &projects {
+        let commits = history::commits(project)?;
+
+        for commit in &commits {
+            // Projects branched from each other share their early commits
+            if !seen_commits.insert(commit.hash.clone()) {
+                continue;
+            }
+            let activity = authors.entry(commit.author.clone()).or_default();
+            activity.commits += 1;
+            if commit.timestamp >= now - 30 * DAY {
+                activity.commits_30d += 1;
+            }
+        }
+
+        // A transferred ticket shows as removed on its old project, so the
+        // copy that still exists wins whichever project comes first
+        for ticket in history::reconstruct(&commits) {
+            match seen.get(&ticket.id) {
+                None => {
+                    seen.insert(ticket.id.clone(), tickets.len());
+                    tickets.push((project.clone(), ticket));
+                }
+                Some(&i) if tickets[i].1.removed_at.is_some() && ticket.removed_at.is_none() => {
+                    tickets[i] = (project.clone(), ticket);
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    for (_, ticket) in &tickets {
+        authors.entry(ticket.created_by.clone()).or_default().created += 1;
+    }
+
+    Ok(Stats {
+        projects,
+        tickets,
+        authors,
+    })
+}
+
+/// Render the summary tables
+pub fn render(stats: &Stats) -> String {
+    let now = history::now();
+    let mut out = String::new();
+
+    let _ = writeln!(out, "Projects: {}", stats.projects.join(", "));
+    out.push('\n');
+
+    // Status x priority matrix of current tickets
+    let _ = write!(out, "{:<8}", "STATUS");
+    for priority in PRIORITIES {
+        let _ = write!(out, " {:>5}", format!("{priority:?}"));
+    }
+    let _ = writeln!(out, " {:>6}", "TOTAL");
+
+    let mut column_totals = [0usize; PRIORITIES.len()];
+    for status in Status::iter() {
+        let _ = write!(out, "{:<8}", format!("{status:?}"));
+        let mut row_total = 0;
+        for (i, priority) in PRIORITIES.iter().enumerate() {
+            let count = stats
+                .tickets
+                .iter()
+                .filter(|(_, t)| t.status() == Some(status) && t.priority == *priority)
+                .count();
+            column_totals[i] += count;
+            row_total += count;
+            let _ = write!(out, " {count:>5}");
+        }
+        let _ = writeln!(out, " {row_total:>6}");
+    }
+    let _ = write!(out, "{:<8}", "total");
+    for total in column_totals {
+        let _ = write!(out, " {total:>5}");
+    }
+    let _ = writeln!(out, " {:>6}", column_totals.iter().sum::<usize>());
+    out.push('\n');
+
+    // Throughput over the last 7 and 30 days
+    let created_since = |days: i64| {
+        stats
+            .tickets
+            .iter()
+            .filter(|(_, t)| t.created_at >= now - days * DAY)
+            .count()
+    };
+    let closed_since = |days: i64| {
+        stats
+            .tickets
+            .iter()
+            .filter(|(_, t)| t.done_at().is_some_and(|done| done >= now - days * DAY))
+            .count()
+    };
+    let _ = writeln!(out, "{:<8} {:>5} {:>5}", "", "7d", "30d");
+    let _ = writeln!(out, "{:<8} {:>5} {:>5}", "created", created_since(7), created_since(30));
+    let _ = writeln!(out, "{:<8} {:>5} {:>5}", "closed", closed_since(7), closed_since(30));
+    out.push('\n');
+
+    // Oldest tickets that are not done
+    let mut open: Vec<&(String, TicketHistory)> = stats
+        .tickets
+        .iter()
+        .filter(|(_, t)| t.status().is_some_and(|s| s != Status::done))
+        .collect();
+    open.sort_by_key(|(_, t)| t.created_at);
+
+    let _ = writeln!(out, "Oldest open tickets");
+    if open.is_empty() {
+        let _ = writeln!(out, "  none");
+    }
+    for (project, ticket) in open.iter().take(OLDEST_LIMIT) {
+        let title = git::read_file(project, &format!("{}/title.md", ticket.id)).unwrap_or_default();
+        let status = ticket.status().unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  {} {:>4}d [{:?}] {:<7} {}",
+            ticket.id,
+            (now - ticket.created_at) / DAY,
+            ticket.priority,
+            format!("{status:?}"),
+            title.trim(),
+        );
+    }
+    out.push('\n');
+
+    // Per-author activity
+    let author_width = stats
+        .authors
+        .keys()
+        .map(|a| a.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("AUTHOR".len());
+    let _ = writeln!(
+        out,
+        "{:<author_width$} {:>7} {:>7} {:>7}",
+        "AUTHOR", "COMMITS", "30D", "CREATED"
+    );
+    for (author, activity) in &stats.authors {
+        let _ = writeln!(
+            out,
+            "{:<author_width$} {:>7} {:>7} {:>7}",
+            author, activity.commits, activity.commits_30d, activity.created
+        );
+    }
+
+    out
+}
@@ -0,0 +1,127 @@
+use thiserror::Error;
+
+use crate::ffi::TixError;
+use crate::git::{self, GitError};
+use crate::history::is_ticket_id;
+
+#[derive(Debug, Error)]
+pub enum TransferError {
+    #[error(transparent)]
+    TixError(#[from] TixError),
+
+    #[error(transparent)]
+    GitError(#[from] GitError),
+
+    #[error("Ticket {0} is already in project '{1}'")]
+    SameProject(String, String),
+
+    #[error("Ticket {0} already exists in project '{1}'")]
+    AlreadyExists(String, String),
+
+    #[error("Failed to create a temporary index: {0}")]
+    TempIndex(std::io::Error),
+}
+
+/// Move a ticket from the current project to another project branch.
+///
+/// The ticket directory is committed onto the target branch through a
+/// temporary index so the working tree never switches, then removed from
+/// the current project. Each commit message names the other commit.
+pub fn transfer(ticket_id: &str, target: &str) -> Result<String, TransferError> {
+    // The ID is used as a pathspec below, where "." would mean every ticket
+    if !is_ticket_id(ticket_id) {
+        return Err(TixError::InvalidTicketId.into());
+    }
+    let source = git::current_branch()?;
+    if source == target {
+        return Err(TransferError::SameProject(
+            ticket_id.to_string(),
+            target.to_string(),
+        ));
+    }
+    git::ensure_project(target)?;
+
+    if git::git(&["ls-tree", "--name-only", &source, "--", ticket_id])?
+        .trim()
+        .is_empty()
+    {
+        return Err(TixError::TicketNotFound.into());
+    }
+    if !git::git(&["ls-tree", "--name-only", target, "--", ticket_id])?
+        .trim()
+        .is_empty()
+    {
+        return Err(TransferError::AlreadyExists(
+            ticket_id.to_string(),
+            target.to_string(),
+        ));
+    }
+
+    let source_head = git::git(&["rev-parse", "HEAD"])?.trim().to_string();
+    let target_head = git::git(&["rev-parse", target])?.trim().to_string();
+
+    // Build the target tree: the target branch plus the ticket directory,
+    // in an index of its own that is removed with the directory
+    let scratch = tempfile::Builder::new()
+        .prefix("tix_transfer_")
+        .tempdir()
+        .map_err(TransferError::TempIndex)?;
+    let index = scratch.path().join("index");
+    let target_commit = (|| {
+        git::git_with_index(&index, &["read-tree", target])?;
+        git::git_with_index(
+            &index,
+            &[
+                "read-tree",
+                &format!("--prefix={ticket_id}/"),
+                &format!("{source_head}:{ticket_id}"),
+            ],
+        )?;
+        let tree = git::git_with_index(&index, &["write-tree"])?;
+        let message = format!(
+            "transfer {ticket_id} from {source}\n\nTransferred from project '{source}' at {source_head}"
+        );
+        git::git(&[
+            "commit-tree",
+            tree.trim(),
+            "-p",
+            &target_head,
+            "-m",
+            &message,
+        ])
+    })();
+    drop(scratch);
+    let target_commit = target_commit?.trim().to_string();
+
+    git::git(&[
+        "update-ref",
+        &format!("refs/heads/{target}"),
+        &target_commit,
+        &target_head,
+    ])?;
+
+    // Remove the ticket from the current project, or take it back out of
+    // the target so it never ends up in both
+    let removed = (|| {
+        git::git(&["rm", "-r", "-q", "--", ticket_id])?;
+        let message = format!(
+            "transfer {ticket_id} to {target}\n\nTransferred to project '{target}' as {target_commit}"
+        );
+        git::git(&["commit", "-q", "-m", &message])
+    })();
+    if let Err(err) = removed {
+        git::git(&[
+            "update-ref",
+            &format!("refs/heads/{target}"),
+            &target_head,
+            &target_commit,
+        ])
+        .ok();
+        git::rollback(&source_head).ok();
+        return Err(err.into());
+    }
+
+    Ok(format!(
+        "Transferred ticket {ticket_id} from {source} to {target}"
+    ))
+}
@@ -0,0 +1,120 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+
+/// How much tix reports on stderr, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    /// Errors only (-q)
+    Quiet,
+    /// Errors and progress messages
+    Normal,
+    /// Also what tix is doing: workspace, config files, git commands (-v)
+    Verbose,
+    /// Also every tix library call with its arguments and result (-vv)
+    Debug,
+}
+
+impl Level {
+    /// Parse TIX_LOG: quiet, normal, verbose or debug
+    fn parse(value: &str) -> Option<Level> {
+        match value.trim().to_lowercase().as_str() {
+            "quiet" | "error" => Some(Level::Quiet),
+            "normal" | "warn" => Some(Level::Normal),
+            "verbose" | "info" => Some(Level::Verbose),
+            "debug" | "trace" => Some(Level::Debug),
+            _ => None,
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Normal as u8);
+static TIMINGS: AtomicBool = AtomicBool::new(false);
+
+/// Calls timed since startup, in the order they finished
+static CALLS: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+/// Set the level from -q/-v, falling back to TIX_LOG
+pub fn init(quiet: bool, verbose: u8, timings: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => Level::Quiet,
+        (false, 0) => std::env::var("TIX_LOG")
+            .ok()
+            .and_then(|value| Level::parse(&value))
+            .unwrap_or(Level::Normal),
+        (false, 1) => Level::Verbose,
+        (false, _) => Level::Debug,
+    };
+    LEVEL.store(level as u8, Ordering::Relaxed);
+    TIMINGS.store(timings, Ordering::Relaxed);
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Print to stderr at the given level
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        if $crate::verbosity::enabled($level) {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+/// Progress and side notes, hidden by -q
+macro_rules! note {
+    ($($arg:tt)*) => {
+        $crate::verbosity::log!($crate::verbosity::Level::Normal, $($arg)*)
+    };
+}
+
+/// Report what tix is doing, shown with -v
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::verbosity::log!($crate::verbosity::Level::Verbose, "tix: {}", format_args!($($arg)*))
+    };
+}
+
+/// Low-level trace, shown with -vv
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::verbosity::log!($crate::verbosity::Level::Debug, "debug: {}", format_args!($($arg)*))
+    };
+}
+
+pub(crate) use {debug, info, log, note};
+
+/// Time a call for --timings
+pub fn timed<T>(name: impl FnOnce() -> String, f: impl FnOnce() -> T) -> T {
+    if !TIMINGS.load(Ordering::Relaxed) {
+        return f();
+    }
+    let start = std::time::Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    CALLS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((name(), elapsed));
+    result
+}
+
+/// Print every timed call and the total, if --timings was given
+pub fn report_timings(total: Duration) {
+    if !TIMINGS.load(Ordering::Relaxed) {
+        return;
+    }
+    let calls = CALLS.lock().unwrap_or_else(|e| e.into_inner());
+    let width = calls
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("total".len());
+
+    for (name, duration) in calls.iter() {
+        eprintln!("{name:<width$}  {:>10.2?}", duration);
+    }
+    eprintln!("{:<width$}  {:>10.2?}", "total", total);
+}
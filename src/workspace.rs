@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directory the tix library keeps its ticket repository in
+pub const TIX_DIR_NAME: &str = ".tix";
+
+struct Workspace {
+    /// Directory the search started from
+    start: PathBuf,
+    /// Directory containing .tix, if one was found
+    root: Option<PathBuf>,
+}
+
+static WORKSPACE: OnceLock<Workspace> = OnceLock::new();
+
+/// Find the closest directory at or above `start` that contains .tix
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(TIX_DIR_NAME).is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// Resolve the workspace and make it the current directory.
+///
+/// Must run before any ffi call, since the tix library works relative to
+/// the current directory. `dir` comes from -C/--workspace or TIX_DIR and may
+/// point at the workspace, any directory inside it, or the .tix directory
+/// itself. When `search` is false (init, clone) no upward search happens.
+pub fn enter(dir: Option<&Path>, search: bool) -> std::io::Result<()> {
+    let start = start_dir(dir)?;
+    let root = if search { discover(&start) } else { None };
+    std::env::set_current_dir(root.as_deref().unwrap_or(&start))?;
+
+    WORKSPACE.get_or_init(|| Workspace { start, root });
+    Ok(())
+}
+
+/// Find the workspace `enter` would use, without entering it
+pub fn locate(dir: Option<&Path>) -> Option<PathBuf> {
+    discover(&start_dir(dir).ok()?)
+}
+
+fn start_dir(dir: Option<&Path>) -> std::io::Result<PathBuf> {
+    let mut start = match dir {
+        Some(dir) => std::path::absolute(dir)?,
+        None => std::env::current_dir()?,
+    };
+    if start.file_name().is_some_and(|name| name == TIX_DIR_NAME) {
+        start.pop();
+    }
+    Ok(start)
+}
+
+/// Directory containing .tix, if the workspace was found
+pub fn root() -> Option<&'static Path> {
+    WORKSPACE.get().and_then(|w| w.root.as_deref())
+}
+
+/// Make a path the user typed relative to where they ran tix absolute,
+/// since `enter` changes into the workspace root
+pub fn resolve(path: &Path) -> PathBuf {
+    match WORKSPACE.get() {
+        Some(workspace) if path.is_relative() => workspace.start.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Human-readable location for error messages
+pub fn describe() -> String {
+    match WORKSPACE.get() {
+        Some(Workspace { root: Some(root), .. }) => root.join(TIX_DIR_NAME).display().to_string(),
+        Some(Workspace { start, root: None }) => {
+            format!("no {TIX_DIR_NAME} in {} or any parent directory", start.display())
+        }
+        None => TIX_DIR_NAME.to_string(),
+    }
+}